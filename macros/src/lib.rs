@@ -0,0 +1,124 @@
+//! # Scoped Ref Macros
+//! 
+//! Procedural macros for the [scoped-ref](https://crates.io/crates/scoped-ref) crate. These should be used through `scoped-ref` (with its "derive" feature) instead of depending on this crate directly
+
+
+
+#![warn(missing_docs)]
+#![forbid(clippy::unwrap_used)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote};
+use syn::{DeriveInput, GenericParam, Ident, Lifetime, Path, WherePredicate, parse_macro_input, spanned::Spanned};
+
+
+
+/// Creates a `TypeConnector` for the given type and links the two with `HasConnector`
+/// 
+/// For a type named `Frame`, this creates a struct named `FrameConnector` (with the same visibility as `Frame`) which represents `&Frame`. The connector can then be named as `FrameConnector` or `ConnectorOf<Frame>`, so every crate that uses `Frame` agrees on the same connector
+/// 
+/// Every lifetime of the type is set to the lifetime of `TypeConnector::Super`, and every type parameter is passed on to the connector (and must be `'static`). The name of the connector can be changed with `#[type_connector(name = OtherName)]`, and the path of the `scoped_ref` crate can be changed with `#[type_connector(crate = path::to::scoped_ref)]`
+#[proc_macro_derive(TypeConnector, attributes(type_connector))]
+pub fn derive_type_connector(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	match derive_type_connector_inner(input) {
+		Ok(output) => output.into(),
+		Err(err) => err.to_compile_error().into(),
+	}
+}
+
+fn derive_type_connector_inner(input: DeriveInput) -> syn::Result<TokenStream2> {
+	let name = &input.ident;
+	let vis = &input.vis;
+	let options = Options::parse(&input)?;
+	let connector_name = options.name.unwrap_or_else(|| format_ident!("{name}Connector"));
+	let crate_path = options.crate_path.unwrap_or_else(|| syn::parse_quote!(::scoped_ref));
+	
+	let mut lifetimes = vec!();
+	let mut params = vec!();
+	let mut param_bounds = vec!();
+	for param in &input.generics.params {
+		match param {
+			GenericParam::Lifetime(param) => lifetimes.push(param.lifetime.clone()),
+			GenericParam::Type(param) => {
+				params.push(param.ident.clone());
+				param_bounds.push(param.bounds.clone());
+			}
+			GenericParam::Const(param) => return Err(syn::Error::new(param.span(), "`TypeConnector` cannot be derived for types with const generics")),
+		}
+	}
+	
+	// predicates that use the type's lifetimes can't be added to the connector's impl, since those lifetimes don't exist there
+	let predicates = input.generics.where_clause.iter().flat_map(|where_clause| &where_clause.predicates).collect::<Vec<_>>();
+	let connector_predicates = predicates.iter().filter(|predicate| !uses_lifetimes(predicate, &lifetimes));
+	
+	let super_lifetime = Lifetime::new("'__scoped_ref", name.span());
+	let super_args = input.generics.params.iter().map(|param| match param {
+		GenericParam::Lifetime(_) => quote!(#super_lifetime),
+		GenericParam::Type(param) => { let ident = &param.ident; quote!(#ident) }
+		GenericParam::Const(_) => unreachable!(),
+	});
+	let (impl_generics, type_generics, _) = input.generics.split_for_impl();
+	let doc = format!("The `TypeConnector` for `{name}`, created by `#[derive(TypeConnector)]`");
+	
+	Ok(quote! {
+		
+		#[doc = #doc]
+		#vis struct #connector_name <#(#params),*> (::core::marker::PhantomData<fn() -> (#(#params,)*)>);
+		
+		impl <#(#params: 'static + #param_bounds),*> #crate_path::TypeConnector for #connector_name <#(#params),*> where #(#connector_predicates,)* {
+			type Super<#super_lifetime> = #name <#(#super_args),*>;
+		}
+		
+		impl #impl_generics #crate_path::HasConnector for #name #type_generics where #(#params: 'static,)* #(#predicates,)* {
+			type Connector = #connector_name <#(#params),*>;
+		}
+		
+	})
+}
+
+// the options given with `#[type_connector(..)]`
+#[derive(Default)]
+struct Options {
+	name: Option<Ident>,
+	crate_path: Option<Path>,
+}
+
+impl Options {
+	fn parse(input: &DeriveInput) -> syn::Result<Self> {
+		let mut options = Self::default();
+		for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("type_connector")) {
+			attr.parse_nested_meta(|meta| {
+				if meta.path.is_ident("name") {
+					options.name = Some(meta.value()?.parse()?);
+					Ok(())
+				} else if meta.path.is_ident("crate") {
+					options.crate_path = Some(meta.value()?.call(Path::parse_mod_style)?);
+					Ok(())
+				} else {
+					Err(meta.error("unknown `type_connector` option, expected `name` or `crate`"))
+				}
+			})?;
+		}
+		Ok(options)
+	}
+}
+
+fn uses_lifetimes(predicate: &WherePredicate, lifetimes: &[Lifetime]) -> bool {
+	fn check(tokens: TokenStream2, lifetimes: &[Lifetime]) -> bool {
+		// lifetimes are tokenized as a `'` followed by an ident
+		let mut prev_is_quote = false;
+		for token in tokens {
+			let is_match = match &token {
+				TokenTree::Group(group) => check(group.stream(), lifetimes),
+				TokenTree::Ident(ident) => prev_is_quote && lifetimes.iter().any(|lifetime| lifetime.ident == *ident),
+				TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+			};
+			if is_match { return true; }
+			prev_is_quote = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+		}
+		false
+	}
+	check(quote!(#predicate), lifetimes)
+}
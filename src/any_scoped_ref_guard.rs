@@ -0,0 +1,99 @@
+use crate::*;
+use std::{any::{TypeId, type_name}, marker::PhantomData};
+
+
+
+/// A type-erased [ScopedRefGuard], which allows guards of different types to be stored together (for example, in a `Vec`). To create this type, use `AnyScopedRefGuard::new(guard)` or `guard.into()`
+/// 
+/// Like a guard, the `ScopedRef` that this came from cannot be dropped until this is dropped. The data can still be accessed by giving the original connector type to [AnyScopedRefGuard::downcast_ref()] or [AnyScopedRefGuard::downcast()]. If the data isn't needed at all, [ScopedKeepAlive] can be used instead
+pub struct AnyScopedRefGuard {
+	
+	// the data pointer is type-erased in the scope's header, so only the connector has to be remembered
+	pub(crate) connector_type_id: TypeId,
+	pub(crate) connector_type_name: &'static str,
+	
+	pub(crate) keep_alive: ScopedKeepAlive,
+	
+}
+
+// SAFETY: this can only be created from guards that are `Send + Sync`
+unsafe impl Send for AnyScopedRefGuard {}
+unsafe impl Sync for AnyScopedRefGuard {}
+
+impl AnyScopedRefGuard {
+	
+	/// Erases the type of the given guard
+	pub fn new<ConnectorType: TypeConnector>(data_ref: ScopedRefGuard<ConnectorType>) -> Self where ScopedRefGuard<ConnectorType>: Send + Sync {
+		Self {
+			connector_type_id: TypeId::of::<ConnectorType>(),
+			connector_type_name: type_name::<ConnectorType>(),
+			keep_alive: data_ref.keep_alive,
+		}
+	}
+	
+	/// Returns whether the original guard used the given connector
+	#[inline]
+	pub fn is<ConnectorType: TypeConnector>(&self) -> bool {
+		self.connector_type_id == TypeId::of::<ConnectorType>()
+	}
+	
+	/// Returns the name of the original guard's connector (this is only meant for debugging, see `std::any::type_name()`)
+	#[inline]
+	pub fn connector_type_name(&self) -> &'static str {
+		self.connector_type_name
+	}
+	
+	/// Returns the inner data if the original guard used the given connector, or `None` otherwise. This is the same as [ScopedRefGuard::inner()] for the original guard
+	pub fn downcast_ref<'a, ConnectorType: TypeConnector>(&'a self) -> Option<&'a ConnectorType::Super<'a>> {
+		if !self.is::<ConnectorType>() { return None; }
+		// SAFETY: see `ScopedRefGuard::inner()`
+		Some(unsafe { std::mem::transmute::<&'a ConnectorType::Super<'static>, &'a ConnectorType::Super<'a>>(self.keep_alive.header().data_ptr::<ConnectorType>().as_ref()) })
+	}
+	
+	/// Turns this back into the original guard if it used the given connector, or returns `self` otherwise
+	pub fn downcast<ConnectorType: TypeConnector>(self) -> Result<ScopedRefGuard<ConnectorType>, Self> {
+		if !self.is::<ConnectorType>() { return Err(self); }
+		Ok(ScopedRefGuard {
+			keep_alive: self.keep_alive,
+			phantom: PhantomData,
+		})
+	}
+	
+	/// Returns a new [ScopedKeepAlive], which keeps the `ScopedRef` alive without giving access to its data
+	#[inline]
+	pub fn keep_alive(&self) -> ScopedKeepAlive {
+		self.keep_alive.clone()
+	}
+	
+}
+
+impl<ConnectorType: TypeConnector> From<ScopedRefGuard<ConnectorType>> for AnyScopedRefGuard where ScopedRefGuard<ConnectorType>: Send + Sync {
+	#[inline]
+	fn from(data_ref: ScopedRefGuard<ConnectorType>) -> Self {
+		Self::new(data_ref)
+	}
+}
+
+impl From<AnyScopedRefGuard> for ScopedKeepAlive {
+	#[inline]
+	fn from(data_ref: AnyScopedRefGuard) -> Self {
+		data_ref.keep_alive
+	}
+}
+
+impl Clone for AnyScopedRefGuard {
+	#[inline]
+	fn clone(&self) -> Self {
+		Self {
+			connector_type_id: self.connector_type_id,
+			connector_type_name: self.connector_type_name,
+			keep_alive: self.keep_alive.clone(),
+		}
+	}
+}
+
+impl std::fmt::Debug for AnyScopedRefGuard {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("AnyScopedRefGuard").field("connector", &self.connector_type_name).finish_non_exhaustive()
+	}
+}
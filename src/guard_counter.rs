@@ -0,0 +1,209 @@
+// Counts the active guards of a `ScopedRef`. By default, this is a single atomic integer, and with the "sharded-counter" feature, it's split into several cache-padded stripes so that guards on different threads don't fight over one cache line
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "sharded-counter")]
+use std::sync::{Mutex, PoisonError, atomic::AtomicUsize};
+
+
+
+/// The integer type that guard limits are given in, which is `u32` by default and `u64` with the "guard-count-u64" feature
+#[cfg(not(feature = "guard-count-u64"))]
+pub type GuardCount = u32;
+/// The integer type that guard limits are given in, which is `u32` by default and `u64` with the "guard-count-u64" feature
+#[cfg(feature = "guard-count-u64")]
+pub type GuardCount = u64;
+
+// The lower bits of the counter hold the number of active guards, the highest bit is set while something is waiting for the counter to change (so guards only have to wake waiters when this is set), and the bits in between are the waiter epoch (see `GuardCounter::set_has_waiters()`). The whole counter is 64 bits, so that the epoch has plenty of room either way
+#[cfg(not(feature = "guard-count-u64"))]
+const COUNT_BITS: u32 = 32;
+#[cfg(feature = "guard-count-u64")]
+const COUNT_BITS: u32 = 48;
+const GUARD_COUNT_MASK: u64 = (1 << (COUNT_BITS - 1)) - 1;
+#[cfg(not(feature = "sharded-counter"))]
+const WAITER_EPOCH_MASK: u64 = !(u64::MAX >> 1) - (1 << COUNT_BITS);
+#[cfg(not(feature = "sharded-counter"))]
+const HAS_WAITERS: u64 = !(u64::MAX >> 1);
+// like `Arc`, the program is aborted once the counter goes past this, which leaves plenty of room for the guards that are created before the abort happens
+const MAX_COUNT: u64 = GUARD_COUNT_MASK >> 1;
+/// The maximum number of active guards that a scope can have, which is `2^30 - 1` by default and `2^46 - 1` with the "guard-count-u64" feature (the program is aborted if a scope goes past this)
+pub const MAX_GUARD_COUNT: GuardCount = MAX_COUNT as GuardCount;
+
+#[cold]
+fn guard_count_overflow() -> ! {
+	eprintln!("Program must be aborted due to too many active `ScopedRefGuard`s (guards are probably being leaked)");
+	std::process::abort();
+}
+
+
+
+#[cfg(not(feature = "sharded-counter"))]
+pub(crate) struct GuardCounter {
+	count: AtomicU64,
+}
+
+#[cfg(not(feature = "sharded-counter"))]
+impl GuardCounter {
+	
+	pub(crate) fn new() -> Self {
+		Self { count: AtomicU64::new(0) }
+	}
+	
+	// returns whether this is the first active guard
+	#[inline]
+	pub(crate) fn increment(&self) -> bool {
+		let prev_count = self.count.fetch_add(1, Ordering::AcqRel);
+		if prev_count & GUARD_COUNT_MASK >= MAX_COUNT { guard_count_overflow(); }
+		prev_count & GUARD_COUNT_MASK == 0
+	}
+	
+	// returns the whole state of the counter, which is needed for `try_decrement()`
+	#[inline]
+	pub(crate) fn state(&self) -> CounterState {
+		CounterState(self.count.load(Ordering::Acquire))
+	}
+	
+	// Decrements the counter if it still has the given state, and otherwise returns the new state. Since every new waiter changes the state (see `set_has_waiters()`), this fails if a waiter was registered after `state` was read, so a guard can't miss waiters that it hasn't seen
+	#[inline]
+	pub(crate) fn try_decrement(&self, state: CounterState) -> Result<(), CounterState> {
+		self.count.compare_exchange_weak(state.0, state.0 - 1, Ordering::AcqRel, Ordering::Acquire).map(|_| ()).map_err(CounterState)
+	}
+	
+	// increments the counter if there are fewer active guards than `max_guards`, and returns whether this is the first active guard
+	#[allow(clippy::useless_conversion)] // `GuardCount` is a `u64` with "guard-count-u64"
+	pub(crate) fn try_admit(&self, max_guards: GuardCount) -> Option<bool> {
+		let prev_count = self.count.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+			((count & GUARD_COUNT_MASK) < u64::from(max_guards)).then_some(count + 1)
+		}).ok()?;
+		Some(prev_count & GUARD_COUNT_MASK == 0)
+	}
+	
+	// This must only be called while the list of waiters is locked, so that the flag always matches the list. Setting the flag also advances the waiter epoch, which makes sure that the counter changes even if the flag was already set
+	pub(crate) fn set_has_waiters(&self, has_waiters: bool) {
+		if has_waiters {
+			let _ = self.count.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+				let epoch = count.wrapping_add(1 << COUNT_BITS) & WAITER_EPOCH_MASK;
+				Some(count & !WAITER_EPOCH_MASK | epoch | HAS_WAITERS)
+			});
+		} else {
+			self.count.fetch_and(!HAS_WAITERS, Ordering::AcqRel);
+		}
+	}
+	
+	#[inline]
+	pub(crate) fn count(&self) -> GuardCount {
+		(self.count.load(Ordering::Acquire) & GUARD_COUNT_MASK) as GuardCount
+	}
+	
+}
+
+
+
+#[cfg(not(feature = "sharded-counter"))]
+#[derive(Clone, Copy)]
+pub(crate) struct CounterState(u64);
+
+#[cfg(not(feature = "sharded-counter"))]
+impl CounterState {
+	#[inline]
+	pub(crate) fn count(self) -> GuardCount {
+		(self.0 & GUARD_COUNT_MASK) as GuardCount
+	}
+	#[inline]
+	pub(crate) fn has_waiters(self) -> bool {
+		self.0 & HAS_WAITERS != 0
+	}
+}
+
+
+
+#[cfg(feature = "sharded-counter")]
+const STRIPE_COUNT: usize = 16;
+// checking the sum of every stripe is slow, so overflow is only checked once a stripe has grown by this much since the last check
+#[cfg(feature = "sharded-counter")]
+const OVERFLOW_CHECK_INTERVAL: u64 = 1 << 16;
+
+#[cfg(feature = "sharded-counter")]
+#[repr(align(128))]
+struct CachePadded<T>(T);
+
+#[cfg(feature = "sharded-counter")]
+static NEXT_STRIPE: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "sharded-counter")]
+thread_local! {
+	static THREAD_STRIPE: usize = NEXT_STRIPE.fetch_add(1, Ordering::Relaxed) % STRIPE_COUNT;
+}
+
+// A guard can be dropped on a different thread than the one it was created on, so a single stripe can keep growing (or go below zero) forever. The counts wrap around, but the wrapping sum of every stripe is always the number of active guards
+#[cfg(feature = "sharded-counter")]
+struct Stripe {
+	count: AtomicU64,
+	// this is incremented before every change to the count, so that `GuardCounter::count()` can tell whether the stripe changed between two reads (the count alone can have the same value twice)
+	version: AtomicU64,
+}
+
+#[cfg(feature = "sharded-counter")]
+pub(crate) struct GuardCounter {
+	stripes: [CachePadded<Stripe>; STRIPE_COUNT],
+	// `try_admit()` has to read the count and increment it without other admissions happening in between
+	admission_lock: Mutex<()>,
+}
+
+#[cfg(feature = "sharded-counter")]
+impl GuardCounter {
+	
+	pub(crate) fn new() -> Self {
+		Self {
+			stripes: std::array::from_fn(|_| CachePadded(Stripe { count: AtomicU64::new(0), version: AtomicU64::new(0) })),
+			admission_lock: Mutex::new(()),
+		}
+	}
+	
+	#[inline]
+	fn stripe(&self) -> &Stripe {
+		// guards can be dropped while thread locals are being destroyed, so this falls back to the first stripe
+		&self.stripes[THREAD_STRIPE.try_with(|stripe| *stripe).unwrap_or(0)].0
+	}
+	
+	// returns whether this is the first active guard, which can't be known with this counter
+	#[inline]
+	pub(crate) fn increment(&self) -> bool {
+		let stripe = self.stripe();
+		stripe.version.fetch_add(1, Ordering::SeqCst);
+		let prev_count = stripe.count.fetch_add(1, Ordering::SeqCst);
+		// a single stripe can be far above (or below) the number of active guards when guards are created on one thread and dropped on another, and the guards can also be spread over every stripe, so only the sum is compared to the maximum
+		if prev_count.is_multiple_of(OVERFLOW_CHECK_INTERVAL) && self.count() >= MAX_GUARD_COUNT { guard_count_overflow(); }
+		false
+	}
+	
+	// guards never wake waiters with this counter (waiters poll it instead), so changing the count is the last time that a guard touches the `ScopedRef`
+	#[inline]
+	pub(crate) fn decrement(&self) {
+		let stripe = self.stripe();
+		stripe.version.fetch_add(1, Ordering::SeqCst);
+		stripe.count.fetch_sub(1, Ordering::SeqCst);
+	}
+	
+	// increments the counter if there are fewer active guards than `max_guards`, and returns whether this is the first active guard
+	pub(crate) fn try_admit(&self, max_guards: GuardCount) -> Option<bool> {
+		let _lock = self.admission_lock.lock().unwrap_or_else(PoisonError::into_inner);
+		if self.count() >= max_guards { return None; }
+		Some(self.increment())
+	}
+	
+	// Reads every count between two reads of every version, until no version changes. Since versions are incremented before counts, every change that the counts depend on is seen, so the sum is the number of active guards at one moment. This saturates at `GuardCount::MAX`, since the sum can be larger than a `GuardCount` before the overflow check in `increment()` catches it
+	#[allow(clippy::useless_conversion)] // `GuardCount` is a `u64` with "guard-count-u64"
+	pub(crate) fn count(&self) -> GuardCount {
+		let read_versions = || self.stripes.each_ref().map(|stripe| stripe.0.version.load(Ordering::SeqCst));
+		let mut versions = read_versions();
+		loop {
+			let count = self.stripes.iter().fold(0u64, |count, stripe| count.wrapping_add(stripe.0.count.load(Ordering::SeqCst)));
+			let new_versions = read_versions();
+			if new_versions == versions {
+				return GuardCount::try_from(count).unwrap_or(GuardCount::MAX);
+			}
+			versions = new_versions;
+		}
+	}
+	
+}
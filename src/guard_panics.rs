@@ -0,0 +1,100 @@
+use std::{any::Any, cell::RefCell, sync::{Mutex, Once, PoisonError, atomic::{AtomicU32, Ordering}}};
+
+
+
+/// Describes the guards of a `ScopedRef` that were dropped because of a panic (for example, a thread holding a `ScopedRefGuard` panicked and the guard was dropped while unwinding). This is returned by [ScopedRef::await_guards()](crate::ScopedRef::await_guards) and [ScopedRef::guard_panics()](crate::ScopedRef::guard_panics)
+/// 
+/// NOTE: `ScopedRef` has no `close()` that returns this, since a scope is closed by dropping it (which can't return anything). Calling `await_guards(None)` right before the end of the scope does the same thing, and the drop then returns immediately
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuardPanics {
+	/// The number of guards that were dropped during a panic
+	pub count: u32,
+	/// The message of the first panic that dropped a guard, if there was one and it could be read. This is only captured after [capture_panic_messages()] is called
+	pub first_message: Option<String>,
+}
+
+impl GuardPanics {
+	/// Returns whether any guard was dropped during a panic
+	#[inline]
+	pub fn any(&self) -> bool {
+		self.count > 0
+	}
+	
+	/// Adds the guards from another report to this one (keeping this report's first message if it has one). The count saturates at `u32::MAX`
+	pub fn merge(&mut self, other: GuardPanics) {
+		self.count = self.count.saturating_add(other.count);
+		if self.first_message.is_none() {
+			self.first_message = other.first_message;
+		}
+	}
+}
+
+
+
+// shared between a `ScopedRef` and its guards, guards write to this when they are dropped during a panic
+pub(crate) struct PanicRecord {
+	pub(crate) count: AtomicU32,
+	pub(crate) first_message: Mutex<Option<String>>,
+}
+
+impl PanicRecord {
+	
+	pub(crate) fn new() -> Self {
+		Self {
+			count: AtomicU32::new(0),
+			first_message: Mutex::new(None),
+		}
+	}
+	
+	// NOTE: this must be called before the guard's counter is decremented, since the `ScopedRef` (and therefore this record) might be dropped right after that
+	#[cold]
+	pub(crate) fn record(&self) {
+		self.count.fetch_add(1, Ordering::AcqRel);
+		let mut first_message = self.first_message.lock().unwrap_or_else(PoisonError::into_inner);
+		if first_message.is_none() {
+			*first_message = LAST_PANIC_MESSAGE.with_borrow(Clone::clone);
+		}
+	}
+	
+	// used when the payload of a panic is available after the fact (see `ScopedJoinSet`), which doesn't change the count since the guard already recorded itself
+	pub(crate) fn record_payload(&self, payload: &(dyn Any + Send)) {
+		let mut first_message = self.first_message.lock().unwrap_or_else(PoisonError::into_inner);
+		if first_message.is_none() {
+			*first_message = panic_message(payload);
+		}
+	}
+	
+	pub(crate) fn report(&self) -> GuardPanics {
+		GuardPanics {
+			count: self.count.load(Ordering::Acquire),
+			first_message: self.first_message.lock().unwrap_or_else(PoisonError::into_inner).clone(),
+		}
+	}
+	
+}
+
+
+
+thread_local! {
+	static LAST_PANIC_MESSAGE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Wraps the current panic hook so that panic messages can be given in [GuardPanics::first_message] (which is `None` otherwise). `Drop` can only see that a panic is happening (not why), so this remembers the message of the latest panic on each thread
+/// 
+/// NOTE: this replaces the process-wide panic hook (the previous hook is still called afterwards), so it should be called after any custom panic hook is set, since a hook that is set later replaces this one. Calling this more than once does nothing
+pub fn capture_panic_messages() {
+	static INSTALL: Once = Once::new();
+	INSTALL.call_once(|| {
+		let prev_hook = std::panic::take_hook();
+		std::panic::set_hook(Box::new(move |info| {
+			let message = panic_message(info.payload());
+			let _ = LAST_PANIC_MESSAGE.try_with(|last_message| *last_message.borrow_mut() = message);
+			prev_hook(info);
+		}));
+	});
+}
+
+// panics created with `panic!()` have either a `&str` or a `String` payload
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> Option<String> {
+	payload.downcast_ref::<&str>().map(|message| message.to_string()).or_else(|| payload.downcast_ref::<String>().cloned())
+}
@@ -0,0 +1,94 @@
+use crate::*;
+use std::sync::Arc;
+
+
+
+/// A single `'static` handle which keeps several [ScopedRef]s alive (which can come from different scopes), with typed access to each one's data
+/// 
+/// The guards are stored in an `Arc`, so cloning a `GuardSet` is a single atomic operation no matter how many guards it holds. Every `ScopedRef` in the set is released once every clone of the set is dropped
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// let config = Config::load();
+/// make_scoped_ref!(scoped_config = &config);
+/// let mut guards = GuardSet::new();
+/// guards.push(scoped_config.new_ref());
+/// {
+///     let input = get_input();
+///     make_scoped_ref!(scoped_input = &*input);
+///     guards.push(scoped_input.new_ref());
+///     std::thread::spawn(move || {
+///         let config = guards.get::<Ref<Config>>().unwrap();
+///         let input = guards.get::<Slice<u8>>().unwrap();
+///     });
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GuardSet {
+	pub(crate) guards: Arc<Vec<AnyScopedRefGuard>>,
+}
+
+impl GuardSet {
+	
+	/// Creates an empty set
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	/// Adds a guard to the set. If this set has been cloned, the guards are copied into a new allocation first (which doesn't affect the clones)
+	pub fn push(&mut self, data_ref: impl Into<AnyScopedRefGuard>) {
+		Arc::make_mut(&mut self.guards).push(data_ref.into());
+	}
+	
+	/// Adds a guard to the set and returns the set, which allows chaining
+	#[inline]
+	pub fn with(mut self, data_ref: impl Into<AnyScopedRefGuard>) -> Self {
+		self.push(data_ref);
+		self
+	}
+	
+	/// Returns the data of the first guard that used the given connector, or `None` if there isn't one
+	pub fn get<'a, ConnectorType: TypeConnector>(&'a self) -> Option<&'a ConnectorType::Super<'a>> {
+		self.guards.iter().find_map(AnyScopedRefGuard::downcast_ref::<ConnectorType>)
+	}
+	
+	/// Returns the data of every guard that used the given connector, in the order that they were added
+	pub fn get_all<'a, ConnectorType: TypeConnector>(&'a self) -> impl Iterator<Item = &'a ConnectorType::Super<'a>> {
+		self.guards.iter().filter_map(AnyScopedRefGuard::downcast_ref::<ConnectorType>)
+	}
+	
+	/// Returns every guard in the set, in the order that they were added
+	#[inline]
+	pub fn guards(&self) -> &[AnyScopedRefGuard] {
+		&self.guards
+	}
+	
+	/// Returns the number of guards in the set
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.guards.len()
+	}
+	
+	/// Returns whether the set has no guards
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.guards.is_empty()
+	}
+	
+}
+
+impl<Guard: Into<AnyScopedRefGuard>> FromIterator<Guard> for GuardSet {
+	fn from_iter<T: IntoIterator<Item = Guard>>(iter: T) -> Self {
+		Self {
+			guards: Arc::new(iter.into_iter().map(Into::into).collect()),
+		}
+	}
+}
+
+impl<Guard: Into<AnyScopedRefGuard>> Extend<Guard> for GuardSet {
+	fn extend<T: IntoIterator<Item = Guard>>(&mut self, iter: T) {
+		Arc::make_mut(&mut self.guards).extend(iter.into_iter().map(Into::into));
+	}
+}
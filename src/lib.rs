@@ -68,6 +68,10 @@
 //! - `"unsafe-drop-does-nothing"`: Causes the drop function of `ScopedRef` to do nothing, even if there are still guards active.
 //! - `"unwind-does-abort"` *: Causes `ScopedRef` to abort the program if dropped during a panic unwind. This is to ensure no danging pointers are created
 //! - `"unsafe-ignore-unwind"`: This is the opposite of the "unwind-does-abort" feature. If it is enabled, `ScopedRef`'s drop function will not check for unwinds and will proceed as dictated by the 'drop-does-' features
+//! - `"rayon"`: Adds rayon's parallel iterators to guards of slices and adds `ScopedRef::install()`, which runs rayon jobs that hold a guard
+//! - `"derive"`: Adds `#[derive(TypeConnector)]`, which creates a canonical `TypeConnector` for a type
+//! - `"guard-count-u64"`: Uses a larger counter for active guards, which raises [MAX_GUARD_COUNT] from `2^30 - 1` to `2^46 - 1` and makes [GuardCount] a `u64` (in either case, the program is aborted if a scope goes past `MAX_GUARD_COUNT`)
+//! - `"sharded-counter"`: Splits the guard counter into cache-padded stripes, so that guards created and dropped on many threads at once don't all contend on one atomic. This makes checking the number of active guards slower (so waiting for guards polls the counter instead of being woken), and `on_idle`/`on_busy` callbacks can't be used (setting them panics)
 //! 
 //! '*' = enabled by default
 
@@ -76,9 +80,6 @@
 #![warn(missing_docs)]
 #![forbid(clippy::unwrap_used)]
 
-#![allow(incomplete_features)]
-#![feature(generic_const_exprs)]
-
 
 
 /// Everything about the `ScopedRef` type
@@ -87,13 +88,61 @@ pub use scoped_ref::*;
 /// Everything about the `ScopedRefGuard` type
 pub mod scoped_ref_guard;
 pub use scoped_ref_guard::*;
+/// Everything about the `ScopedRefBuilder` type
+pub mod scoped_ref_builder;
+pub use scoped_ref_builder::*;
+/// Everything about the `ScopedOwned` type
+pub mod scoped_owned;
+pub use scoped_owned::*;
+/// Everything about the `ReusableScopedRef` type
+pub mod reusable_scoped_ref;
+pub use reusable_scoped_ref::*;
+/// Everything about the `ScopedSwap` type
+pub mod scoped_swap;
+pub use scoped_swap::*;
+/// Everything about the `LocalScopedRef` and `LocalScopedRefGuard` types
+pub mod local_scoped_ref;
+pub use local_scoped_ref::*;
+/// Everything about the `SubScope` type
+pub mod sub_scope;
+pub use sub_scope::*;
+/// Everything about the `AnyScopedRefGuard` type
+pub mod any_scoped_ref_guard;
+pub use any_scoped_ref_guard::*;
+/// Everything about the `GuardSet` type
+pub mod guard_set;
+pub use guard_set::*;
 /// Everything about the `TypeConnector` trait and macro
 pub mod type_connector;
 pub use type_connector::*;
+/// Everything about reporting panics from guard holders
+pub mod guard_panics;
+pub use guard_panics::*;
+/// Everything about the `ScopedJoinSet` type
+pub mod scoped_join_set;
+pub use scoped_join_set::*;
+/// Everything about the `ScopedCallback` type
+pub mod scoped_callback;
+pub use scoped_callback::*;
+/// Everything about the `WorkerPool` type
+pub mod pool;
+pub use pool::*;
+/// Everything about the rayon integration (requires the "rayon" feature)
+#[cfg(feature = "rayon")]
+pub mod parallel;
+// Everything about counting active guards
+mod guard_counter;
+pub(crate) use guard_counter::*;
+pub use guard_counter::{GuardCount, MAX_GUARD_COUNT};
+// Everything that a `ScopedRef` shares with its guards
+mod scope_header;
+pub(crate) use scope_header::*;
 mod tests;
 
 #[cfg(feature = "runtime-tokio")]
 pub use tokio;
+#[cfg(feature = "rayon")]
+pub use rayon;
 
 
 
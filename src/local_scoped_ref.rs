@@ -0,0 +1,280 @@
+use crate::*;
+use std::{cell::{Cell, RefCell}, future::poll_fn, marker::PhantomData, ops::Deref, ptr::NonNull, task::{Poll, Waker}};
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+#[cfg(feature = "no-pin")]
+use std::rc::Rc;
+
+
+
+/// Creates a new [LocalScopedRef] and assigns it to a variable. This uses the format `make_local_scoped_ref!(scope_var_name = (reference_to_scope) as ConnectorType);`
+/// 
+/// Like [make_scoped_ref], this makes sure that the `LocalScopedRef` can't be forgotten (which would allow guards to outlive the data)
+#[macro_export]
+macro_rules! make_local_scoped_ref {
+	($scope:ident = ($input:expr) as $connector:ty) => {
+		// the type annotation allows coercions, like `&String` to `&str` or `&{closure}` to `&dyn Fn(..)`
+		let $scope: &<$connector as $crate::TypeConnector>::Super<'_> = $input;
+		#[cfg(not(feature = "no-pin"))]
+		let $scope = &mut unsafe {
+			let $scope = $crate::LocalScopedRef::<$connector>::new($scope);
+			std::pin::pin!($scope)
+		};
+		#[cfg(feature = "no-pin")]
+		let $scope = &mut unsafe {
+			$crate::LocalScopedRef::<$connector>::new($scope)
+		};
+	};
+}
+
+
+
+/// A single-threaded version of [ScopedRef], for things like tokio's `LocalSet` / `spawn_local()` and GUI event loops. To create this type, the [make_local_scoped_ref] macro is recommended
+/// 
+/// This counts its guards with a `Cell` instead of an atomic, and [LocalScopedRef::await_guards()] is woken by the task's waker instead of a `Sync` notifier, so creating and dropping guards is nearly free. Its guards are `'static` (so they can be moved into `spawn_local()` tasks), but they can never leave the current thread. This also works outside of a tokio runtime, with any executor
+/// 
+/// Since every guard lives on the current thread, dropping this can't wait for them (that would never finish), so guards have to be awaited with [LocalScopedRef::await_guards()] first. If there are still active guards when this is dropped, the program is aborted (or the drop panics / does nothing with the "unsafe-drop-does-panic" / "unsafe-drop-does-nothing" features)
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// let local_set = LocalSet::new();
+/// local_set.run_until(async {
+///     let data = vec!(1u8, 2, 3);
+///     make_local_scoped_ref!(scoped_data = (&*data) as Slice<u8>);
+///     let data_ref = scoped_data.new_ref();
+///     tokio::task::spawn_local(async move { println!("{data_ref:?}") });
+///     scoped_data.await_guards().await;
+/// }).await;
+/// ```
+pub struct LocalScopedRef<'a, ConnectorType: TypeConnector> {
+	
+	// stores the data pointer, the counter, and the waiters together, so that guards only need a single pointer to this (which is an `Rc` when "no-pin" is used)
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) header: LocalScopeHeader<ConnectorType>,
+	#[cfg(feature = "no-pin")]
+	pub(crate) header: Rc<LocalScopeHeader<ConnectorType>>,
+	
+	pub(crate) phantom: PhantomData<&'a ConnectorType>,
+	
+}
+
+pub(crate) struct LocalScopeHeader<ConnectorType: TypeConnector> {
+	// see `ScopedRef::erase_lifetime()`
+	pub(crate) data_ptr: NonNull<ConnectorType::Super<'static>>,
+	pub(crate) count: Cell<u32>,
+	// the tasks waiting in `await_guards()`, which are woken when the last guard is dropped
+	pub(crate) waiters: RefCell<Vec<Waker>>,
+}
+
+impl<'a, ConnectorType: TypeConnector> LocalScopedRef<'a, ConnectorType> {
+	
+	/// NOTE: `LocalScopedRef` is meant to be created using the [make_local_scoped_ref] macro.
+	/// 
+	/// Creates a new `LocalScopedRef` with a given reference
+	/// 
+	/// # Safety
+	/// 
+	/// See [ScopedRef::new()]
+	pub unsafe fn new(data: impl Into<&'a ConnectorType::Super<'a>>) -> Self where &'a ConnectorType::Super<'a>: Copy {
+		let data: &'a ConnectorType::Super<'a> = data.into();
+		let header = LocalScopeHeader {
+			data_ptr: unsafe {
+				// SAFETY: `Super<'a>` and `Super<'static>` only differ in lifetimes, so they always have the same layout
+				std::mem::transmute::<&'a ConnectorType::Super<'a>, &'static ConnectorType::Super<'static>>(data).into()
+			},
+			count: Cell::new(0),
+			waiters: RefCell::new(Vec::new()),
+		};
+		Self {
+			#[cfg(not(feature = "no-pin"))]
+			header,
+			#[cfg(feature = "no-pin")]
+			header: Rc::new(header),
+			phantom: PhantomData,
+		}
+	}
+	
+	/// Returns a new guard that can be used to access `&T` as if it is `&'static T` (see [ScopedRef::new_ref()])
+	#[cfg(not(feature = "no-pin"))]
+	#[inline]
+	pub fn new_ref(self: &Pin<&mut Self>) -> LocalScopedRefGuard<ConnectorType> {
+		let header = &self.as_ref().get_ref().header;
+		header.increment();
+		LocalScopedRefGuard {
+			header: NonNull::from(header),
+			phantom: PhantomData,
+		}
+	}
+	/// Returns a new guard that can be used to access `&T` as if it is `&'static T`
+	#[cfg(feature = "no-pin")]
+	#[inline]
+	pub fn new_ref(&self) -> LocalScopedRefGuard<ConnectorType> {
+		self.header.increment();
+		LocalScopedRefGuard {
+			header: self.header.clone(),
+			phantom: PhantomData,
+		}
+	}
+	
+	/// Waits until all guards have been dropped. This works with any executor, since it only uses the task's waker
+	pub async fn await_guards(&self) {
+		poll_fn(|cx| {
+			if self.header.count.get() == 0 { return Poll::Ready(()); }
+			let mut waiters = self.header.waiters.borrow_mut();
+			if !waiters.iter().any(|waiter| waiter.will_wake(cx.waker())) {
+				waiters.push(cx.waker().clone());
+			}
+			Poll::Pending
+		}).await
+	}
+	
+	/// Returns the number of living `LocalScopedRefGuard`s created from this `LocalScopedRef`
+	#[inline]
+	pub fn active_guard_count(&self) -> u32 {
+		self.header.count.get()
+	}
+	
+	/// Returns whether there are still living `LocalScopedRefGuard`s that would cause dropping this `LocalScopedRef` to abort
+	#[inline]
+	pub fn has_active_guards(&self) -> bool {
+		self.header.count.get() > 0
+	}
+	
+}
+
+impl<ConnectorType: TypeConnector> LocalScopeHeader<ConnectorType> {
+	#[inline]
+	fn increment(&self) {
+		let count = self.count.get();
+		if count == u32::MAX {
+			eprintln!("Program must be aborted due to too many active `LocalScopedRefGuard`s (guards are probably being leaked)");
+			std::process::abort();
+		}
+		self.count.set(count + 1);
+	}
+}
+
+// When `LocalScopedRef` is dropped, it can't wait for its guards (since they can only be dropped by this thread), so it aborts if there are any (unless a different feature is enabled)
+impl<'a, ConnectorType: TypeConnector> Drop for LocalScopedRef<'a, ConnectorType> {
+	fn drop(&mut self) {
+		#[cfg(feature = "unwind-does-abort")]
+		if std::thread::panicking() && self.has_active_guards() {
+			eprintln!("Program must be aborted due to a `LocalScopedRef` being dropped on unwind.");
+			std::process::abort();
+		}
+		#[cfg(any(feature = "drop-does-block", feature = "drop-does-abort"))]
+		{
+			if self.has_active_guards() {
+				eprintln!("Program must be aborted due to a `LocalScopedRef` being dropped while it still has active guards (use `await_guards()` before dropping it)");
+				std::process::abort();
+			}
+		}
+		#[cfg(feature = "unsafe-drop-does-panic")]
+		{
+			if self.has_active_guards() { panic!("Attempting to drop a `LocalScopedRef` while it still has active guards"); }
+		}
+		#[cfg(feature = "unsafe-drop-does-nothing")]
+		{}
+	}
+}
+
+
+
+/// A guard created by a [LocalScopedRef], which is the same as [ScopedRefGuard] except that it can't be sent to other threads
+/// 
+/// Like `ScopedRefGuard`, this is a single pointer, so `Option<LocalScopedRefGuard<_>>` is also pointer-sized
+pub struct LocalScopedRefGuard<ConnectorType: TypeConnector> {
+	
+	// the `NonNull` / `Rc` also makes this type not Send/Sync
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) header: NonNull<LocalScopeHeader<ConnectorType>>,
+	#[cfg(feature = "no-pin")]
+	pub(crate) header: Rc<LocalScopeHeader<ConnectorType>>,
+	
+	pub(crate) phantom: PhantomData<*mut ConnectorType>,
+	
+}
+
+impl<ConnectorType: TypeConnector> LocalScopedRefGuard<ConnectorType> {
+	
+	/// Returns the inner data (see [ScopedRefGuard::inner()])
+	#[inline]
+	pub fn inner<'a>(&'a self) -> &'a ConnectorType::Super<'a> {
+		unsafe {
+			// SAFETY: see `ScopedRefGuard::inner()`
+			std::mem::transmute::<&'a ConnectorType::Super<'static>, &'a ConnectorType::Super<'a>>(self.header().data_ptr.as_ref())
+		}
+	}
+	
+	#[inline]
+	fn header(&self) -> &LocalScopeHeader<ConnectorType> {
+		#[cfg(not(feature = "no-pin"))]
+		{
+			// SAFETY: the `LocalScopedRef` can't be dropped or moved while this is alive
+			unsafe { self.header.as_ref() }
+		}
+		#[cfg(feature = "no-pin")]
+		{ &self.header }
+	}
+	
+}
+
+impl<ConnectorType: TypeConnector> Drop for LocalScopedRefGuard<ConnectorType> {
+	fn drop(&mut self) {
+		let header = self.header();
+		let count = header.count.get() - 1;
+		header.count.set(count);
+		if count == 0 {
+			// the `LocalScopedRef` can't be dropped by another thread, so the header can still be used here
+			let waiters = std::mem::take(&mut *header.waiters.borrow_mut());
+			for waiter in waiters {
+				waiter.wake();
+			}
+		}
+	}
+}
+
+impl<ConnectorType: TypeConnector> Clone for LocalScopedRefGuard<ConnectorType> {
+	#[inline]
+	fn clone(&self) -> Self {
+		self.header().increment();
+		Self {
+			#[cfg(not(feature = "no-pin"))]
+			header: self.header,
+			#[cfg(feature = "no-pin")]
+			header: self.header.clone(),
+			phantom: PhantomData,
+		}
+	}
+}
+
+impl<ConnectorType: TypeConnector> std::fmt::Debug for LocalScopedRefGuard<ConnectorType> where for<'a> ConnectorType::Super<'a>: std::fmt::Debug {
+	#[inline]
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.inner().fmt(f)
+	}
+}
+
+impl<ConnectorType: TypeConnector> std::fmt::Display for LocalScopedRefGuard<ConnectorType> where for<'a> ConnectorType::Super<'a>: std::fmt::Display {
+	#[inline]
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.inner().fmt(f)
+	}
+}
+
+impl<ConnectorType: StaticConnector> Deref for LocalScopedRefGuard<ConnectorType> {
+	type Target = ConnectorType::Target;
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		ConnectorType::as_target(self.inner())
+	}
+}
+
+impl<ConnectorType: StaticConnector> AsRef<ConnectorType::Target> for LocalScopedRefGuard<ConnectorType> {
+	#[inline]
+	fn as_ref(&self) -> &ConnectorType::Target {
+		ConnectorType::as_target(self.inner())
+	}
+}
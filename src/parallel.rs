@@ -0,0 +1,72 @@
+use crate::*;
+use rayon::{ThreadPool, iter::IntoParallelIterator, slice::{Iter, ParallelSlice}};
+
+#[cfg(feature = "runtime-tokio")]
+use tokio::sync::oneshot;
+#[cfg(feature = "runtime-tokio")]
+use std::panic::{self, AssertUnwindSafe};
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+// Allows `guard.par_iter()` (from `rayon::iter::IntoParallelRefIterator`) when the guard references a slice
+impl<'g, ConnectorType: SliceConnector> IntoParallelIterator for &'g ScopedRefGuard<ConnectorType> where ConnectorType::Item: Sync + 'g {
+	type Iter = Iter<'g, ConnectorType::Item>;
+	type Item = &'g ConnectorType::Item;
+	#[inline]
+	fn into_par_iter(self) -> Self::Iter {
+		ConnectorType::as_slice(self.inner()).into_par_iter()
+	}
+}
+
+// Allows `guard.par_chunks()`, `guard.par_windows()`, etc when the guard references a slice
+impl<ConnectorType: SliceConnector> ParallelSlice<ConnectorType::Item> for ScopedRefGuard<ConnectorType> where ConnectorType::Item: Sync {
+	#[inline]
+	fn as_parallel_slice(&self) -> &[ConnectorType::Item] {
+		ConnectorType::as_slice(self.inner())
+	}
+}
+
+
+
+impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> {
+	
+	/// Runs `f` inside the given rayon thread pool with a new guard and blocks until it returns (is async on async runtimes). Any rayon functions used inside `f` (like `par_iter()`) will use the given thread pool
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-none"))]
+	pub fn install<R: Send>(self: &Pin<&mut Self>, pool: &ThreadPool, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> R + Send) -> R where ScopedRefGuard<ConnectorType>: Send {
+		let data_ref = self.new_ref();
+		pool.install(move || f(data_ref))
+	}
+	/// Runs `f` inside the given rayon thread pool with a new guard and blocks until it returns (is async on async runtimes). Any rayon functions used inside `f` (like `par_iter()`) will use the given thread pool
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-none"))]
+	pub fn install<R: Send>(&self, pool: &ThreadPool, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> R + Send) -> R where ScopedRefGuard<ConnectorType>: Send {
+		let data_ref = self.new_ref();
+		pool.install(move || f(data_ref))
+	}
+	/// Runs `f` inside the given rayon thread pool with a new guard and waits until it returns without blocking the async runtime. Any rayon functions used inside `f` (like `par_iter()`) will use the given thread pool
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
+	pub async fn install<R: Send + 'static>(self: &Pin<&mut Self>, pool: &ThreadPool, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> R + Send + 'static) -> R where ScopedRefGuard<ConnectorType>: Send {
+		spawn_on_pool(pool, self.new_ref(), f).await
+	}
+	/// Runs `f` inside the given rayon thread pool with a new guard and waits until it returns without blocking the async runtime. Any rayon functions used inside `f` (like `par_iter()`) will use the given thread pool
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
+	pub async fn install<R: Send + 'static>(&self, pool: &ThreadPool, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> R + Send + 'static) -> R where ScopedRefGuard<ConnectorType>: Send {
+		spawn_on_pool(pool, self.new_ref(), f).await
+	}
+	
+}
+
+#[cfg(feature = "runtime-tokio")]
+async fn spawn_on_pool<ConnectorType: TypeConnector, R: Send + 'static>(pool: &ThreadPool, data_ref: ScopedRefGuard<ConnectorType>, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> R + Send + 'static) -> R where ScopedRefGuard<ConnectorType>: Send {
+	let (sender, receiver) = oneshot::channel();
+	pool.spawn(move || {
+		let output = panic::catch_unwind(AssertUnwindSafe(move || f(data_ref)));
+		let _ = sender.send(output);
+	});
+	match receiver.await.expect("rayon job was dropped before it could finish") {
+		Ok(output) => output,
+		Err(payload) => panic::resume_unwind(payload),
+	}
+}
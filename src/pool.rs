@@ -0,0 +1,152 @@
+use crate::*;
+use std::{thread::{self, JoinHandle}, panic::{self, AssertUnwindSafe}, sync::{Arc, Mutex, PoisonError, mpsc::{self, Sender, Receiver}}};
+
+#[cfg(feature = "runtime-tokio")]
+use tokio::sync::mpsc as tokio_mpsc;
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A minimal pool of long-lived worker threads which run jobs that borrow scoped data through a [ScopedRefGuard]
+/// 
+/// Because each job holds its own guard, the `ScopedRef` that the guard came from cannot be dropped until the job is finished, so the workers can safely use data from the caller's stack. If a job panics, the worker thread survives and the panic is reported through [ScopedRef::guard_panics()]
+pub struct WorkerPool {
+	pub(crate) sender: Option<Sender<Job>>,
+	pub(crate) workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+	
+	/// Creates a new pool with the given number of worker threads
+	pub fn new(thread_count: usize) -> Self {
+		assert!(thread_count > 0, "`WorkerPool` must have at least one thread");
+		let (sender, receiver) = mpsc::channel::<Job>();
+		let receiver = Arc::new(Mutex::new(receiver));
+		let workers = (0..thread_count).map(|_| {
+			let receiver = receiver.clone();
+			thread::spawn(move || run_worker(&receiver))
+		}).collect();
+		Self {
+			sender: Some(sender),
+			workers,
+		}
+	}
+	
+	/// Returns the number of worker threads in this pool
+	#[inline]
+	pub fn thread_count(&self) -> usize {
+		self.workers.len()
+	}
+	
+	/// Runs `f` on one of the worker threads, giving it the data referenced by `data_ref`. The guard is dropped once `f` returns
+	pub fn execute<ConnectorType: TypeConnector>(&self, data_ref: ScopedRefGuard<ConnectorType>, f: impl for<'a> FnOnce(&'a ConnectorType::Super<'a>) + Send + 'static) where ScopedRefGuard<ConnectorType>: Send {
+		self.send(Box::new(move || f(data_ref.inner())));
+	}
+	
+	/// Splits the slice referenced by `data_ref` into chunks of `chunk_size` elements, runs `f` on every element (with one job per chunk), and blocks until every job is finished (is async on async runtimes). If any call to `f` panicked, this resumes that panic after all jobs are finished
+	#[cfg(feature = "runtime-none")]
+	pub fn for_each<ConnectorType: SliceConnector>(&self, data_ref: ScopedRefGuard<ConnectorType>, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static) where ScopedRefGuard<ConnectorType>: Send + Sync {
+		let (sender, receiver) = mpsc::channel();
+		let job_count = self.send_chunks(data_ref, chunk_size, f, move |output| { let _ = sender.send(output); });
+		let mut first_panic = None;
+		for _ in 0..job_count {
+			let output = receiver.recv().expect("worker job was dropped before it could finish");
+			if let Err(payload) = output && first_panic.is_none() {
+				first_panic = Some(payload);
+			}
+		}
+		if let Some(payload) = first_panic {
+			panic::resume_unwind(payload);
+		}
+	}
+	/// Splits the slice referenced by `data_ref` into chunks of `chunk_size` elements, runs `f` on every element (with one job per chunk), and waits until every job is finished without blocking the async runtime. If any call to `f` panicked, this resumes that panic after all jobs are finished
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn for_each<ConnectorType: SliceConnector>(&self, data_ref: ScopedRefGuard<ConnectorType>, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static) where ScopedRefGuard<ConnectorType>: Send + Sync {
+		let (sender, mut receiver) = tokio_mpsc::unbounded_channel();
+		let job_count = self.send_chunks(data_ref, chunk_size, f, move |output| { let _ = sender.send(output); });
+		let mut first_panic = None;
+		for _ in 0..job_count {
+			let output = receiver.recv().await.expect("worker job was dropped before it could finish");
+			if let Err(payload) = output && first_panic.is_none() {
+				first_panic = Some(payload);
+			}
+		}
+		if let Some(payload) = first_panic {
+			panic::resume_unwind(payload);
+		}
+	}
+	
+	// sends one job per chunk and returns the number of jobs sent, `on_finish` is called (on the worker thread) after each job's guard is dropped
+	fn send_chunks<ConnectorType: SliceConnector>(&self, data_ref: ScopedRefGuard<ConnectorType>, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static, on_finish: impl Fn(thread::Result<()>) + Clone + Send + 'static) -> usize where ScopedRefGuard<ConnectorType>: Send + Sync {
+		assert!(chunk_size > 0, "chunk size must be non-zero");
+		let len = ConnectorType::as_slice(data_ref.inner()).len();
+		let f = Arc::new(f);
+		let mut job_count = 0;
+		for start in (0..len).step_by(chunk_size) {
+			let end = usize::min(start + chunk_size, len);
+			let (data_ref, f, on_finish) = (data_ref.clone(), f.clone(), on_finish.clone());
+			self.send(Box::new(move || {
+				let output = panic::catch_unwind(AssertUnwindSafe(move || {
+					ConnectorType::as_slice(data_ref.inner())[start..end].iter().for_each(&*f);
+				}));
+				on_finish(output);
+			}));
+			job_count += 1;
+		}
+		job_count
+	}
+	
+	fn send(&self, job: Job) {
+		self.sender.as_ref().expect("`WorkerPool` was already shut down").send(job).expect("all worker threads have stopped");
+	}
+	
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Job>>) {
+	loop {
+		let job = receiver.lock().unwrap_or_else(PoisonError::into_inner).recv();
+		let Ok(job) = job else { return; }; // the pool was dropped
+		let _ = panic::catch_unwind(AssertUnwindSafe(job));
+	}
+}
+
+// When `WorkerPool` is dropped, it lets the workers finish every job that was already sent, then joins them
+impl Drop for WorkerPool {
+	fn drop(&mut self) {
+		drop(self.sender.take());
+		for worker in self.workers.drain(..) {
+			let _ = worker.join();
+		}
+	}
+}
+
+
+
+impl<'a, ConnectorType: SliceConnector> ScopedRef<'a, ConnectorType> {
+	
+	/// Runs `f` on every element of the referenced slice using the given [WorkerPool] (with one job per chunk of `chunk_size` elements), and blocks until every job is finished (is async on async runtimes)
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-none"))]
+	pub fn par_for_each(self: &Pin<&mut Self>, pool: &WorkerPool, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static) where ScopedRefGuard<ConnectorType>: Send + Sync {
+		pool.for_each(self.new_ref(), chunk_size, f);
+	}
+	/// Runs `f` on every element of the referenced slice using the given [WorkerPool] (with one job per chunk of `chunk_size` elements), and blocks until every job is finished (is async on async runtimes)
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-none"))]
+	pub fn par_for_each(&self, pool: &WorkerPool, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static) where ScopedRefGuard<ConnectorType>: Send + Sync {
+		pool.for_each(self.new_ref(), chunk_size, f);
+	}
+	/// Runs `f` on every element of the referenced slice using the given [WorkerPool] (with one job per chunk of `chunk_size` elements), and waits until every job is finished without blocking the async runtime
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
+	pub async fn par_for_each(self: &Pin<&mut Self>, pool: &WorkerPool, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static) where ScopedRefGuard<ConnectorType>: Send + Sync {
+		pool.for_each(self.new_ref(), chunk_size, f).await;
+	}
+	/// Runs `f` on every element of the referenced slice using the given [WorkerPool] (with one job per chunk of `chunk_size` elements), and waits until every job is finished without blocking the async runtime
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
+	pub async fn par_for_each(&self, pool: &WorkerPool, chunk_size: usize, f: impl Fn(&ConnectorType::Item) + Send + Sync + 'static) where ScopedRefGuard<ConnectorType>: Send + Sync {
+		pool.for_each(self.new_ref(), chunk_size, f).await;
+	}
+	
+}
@@ -0,0 +1,181 @@
+use crate::*;
+use std::{time::Duration, marker::PhantomData};
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+/// Binds a [ReusableScopedRef] to new data and assigns the resulting [ScopedRefBinding] to a variable. This uses the format `rebind_scoped_ref!(reusable_scope_var, binding_var_name = reference_to_scope);`
+/// 
+/// Like [make_scoped_ref], this makes sure that the binding can't be forgotten (which would allow guards to outlive the data)
+#[macro_export]
+macro_rules! rebind_scoped_ref {
+	($reusable:ident, $scope:ident = $input:expr) => {
+		let $scope = $input;
+		let $scope = &mut unsafe {
+			$reusable.rebind($scope)
+		};
+	};
+}
+
+
+
+/// A long-lived scope which can be bound to different data many times (for example, once per frame of a render loop), without creating a new [ScopedRef] each time
+/// 
+/// Each call to [ReusableScopedRef::rebind()] starts a new generation and returns a [ScopedRefBinding], which gives out guards to the new data. When the binding is dropped, it follows the same drop policy as `ScopedRef` (for example, blocking until every guard of that generation is dropped when the "drop-does-block" feature is enabled), so the next generation can only start once the previous one is drained. In debug builds, the generation is also stamped into the scope, so a guard that outlives its binding (which is only possible with the "unsafe-drop-does-*" features) panics when it is used or dropped
+/// 
+/// Unless the "no-pin" feature is enabled, this has to be pinned before it can be used
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// let mut scope = pin!(ReusableScopedRef::<Slice<u8>>::new());
+/// loop {
+///     let frame_data = render_frame();
+///     rebind_scoped_ref!(scope, frame_scope = &*frame_data);
+///     send_to_workers(frame_scope.new_ref());
+/// } // every frame blocks here until the workers drop their guards
+/// ```
+pub struct ReusableScopedRef<ConnectorType: TypeConnector> {
+	// this is only created by the first binding, since a `ScopedRef` always needs data
+	pub(crate) scope: Option<ScopedRef<'static, ConnectorType>>,
+	pub(crate) generation: u64,
+}
+
+impl<ConnectorType: TypeConnector> ReusableScopedRef<ConnectorType> {
+	
+	/// Creates a new `ReusableScopedRef` that isn't bound to any data yet
+	#[inline]
+	pub fn new() -> Self {
+		Self {
+			scope: None,
+			generation: 0,
+		}
+	}
+	
+	/// NOTE: this is meant to be called using the [rebind_scoped_ref] macro.
+	/// 
+	/// Binds this scope to the given data and starts a new generation
+	/// 
+	/// # Safety
+	/// 
+	/// This function is considered unsafe because it is possible to create dangling pointers with this if you 1: create a `ScopedRefBinding` with this, 2: create a `ScopedRefGuard` with the binding, 3: use `std::mem::forget()` to drop the binding, and 4: drop the data that the binding (and therefore the `ScopedRefGuard`) referenced
+	#[cfg(not(feature = "no-pin"))]
+	pub unsafe fn rebind<'b>(self: &'b mut Pin<&mut Self>, data: impl Into<&'b ConnectorType::Super<'b>>) -> ScopedRefBinding<'b, ConnectorType> where &'b ConnectorType::Super<'b>: Copy {
+		// SAFETY: this only changes the data of the inner scope, it never moves it
+		let this = unsafe { self.as_mut().get_unchecked_mut() };
+		unsafe { this.rebind_unchecked(data.into()) }
+	}
+	/// NOTE: this is meant to be called using the [rebind_scoped_ref] macro.
+	/// 
+	/// Binds this scope to the given data and starts a new generation
+	/// 
+	/// # Safety
+	/// 
+	/// This function is considered unsafe because it is possible to create dangling pointers with this if you 1: create a `ScopedRefBinding` with this, 2: create a `ScopedRefGuard` with the binding, 3: use `std::mem::forget()` to drop the binding, and 4: drop the data that the binding (and therefore the `ScopedRefGuard`) referenced
+	#[cfg(feature = "no-pin")]
+	pub unsafe fn rebind<'b>(&'b mut self, data: impl Into<&'b ConnectorType::Super<'b>>) -> ScopedRefBinding<'b, ConnectorType> where &'b ConnectorType::Super<'b>: Copy {
+		unsafe { self.rebind_unchecked(data.into()) }
+	}
+	
+	unsafe fn rebind_unchecked<'b>(&'b mut self, data: &'b ConnectorType::Super<'b>) -> ScopedRefBinding<'b, ConnectorType> {
+		// SAFETY: the binding applies the drop policy before the borrow of the data ends, and it can't be forgotten (see `rebind()`)
+		let data = unsafe { std::mem::transmute::<&'b ConnectorType::Super<'b>, &'static ConnectorType::Super<'static>>(data) };
+		if let Some(scope) = &mut self.scope {
+			// SAFETY: the previous binding waited for its guards before it was dropped (see above), so this can only fail with the "unsafe-drop-does-nothing" feature
+			debug_assert!(!scope.has_active_guards(), "Attempting to rebind a `ReusableScopedRef` while guards from generation {} are still active", self.generation);
+			unsafe { scope.set_data(data); }
+		} else {
+			self.scope = Some(unsafe { ScopedRef::new(data) });
+		}
+		self.generation += 1;
+		let scope = self.scope.as_ref().expect("the scope was just created");
+		scope.header.set_generation(self.generation);
+		ScopedRefBinding {
+			scope,
+			generation: self.generation,
+			phantom: PhantomData,
+		}
+	}
+	
+	/// Returns the number of times that this scope has been bound (which is also the generation of the latest binding)
+	#[inline]
+	pub fn generation(&self) -> u64 {
+		self.generation
+	}
+	
+	/// Returns information about the guards that were dropped during a panic so far, in every generation (see [GuardPanics])
+	#[inline]
+	pub fn guard_panics(&self) -> GuardPanics {
+		self.scope.as_ref().map(ScopedRef::guard_panics).unwrap_or_default()
+	}
+	
+}
+
+impl<ConnectorType: TypeConnector> Default for ReusableScopedRef<ConnectorType> {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+
+
+/// One generation of a [ReusableScopedRef], which gives out guards to the data that it was bound to. To create this type, use the [rebind_scoped_ref] macro
+/// 
+/// When this is dropped, it follows the same drop policy as [ScopedRef]
+pub struct ScopedRefBinding<'b, ConnectorType: TypeConnector> {
+	pub(crate) scope: &'b ScopedRef<'static, ConnectorType>,
+	pub(crate) generation: u64,
+	pub(crate) phantom: PhantomData<&'b ConnectorType::Super<'b>>,
+}
+
+impl<'b, ConnectorType: TypeConnector> ScopedRefBinding<'b, ConnectorType> {
+	
+	/// Returns a new guard that can be used to access the bound data as if it is `'static`
+	#[inline]
+	pub fn new_ref(&self) -> ScopedRefGuard<ConnectorType> {
+		#[cfg(not(feature = "no-pin"))]
+		{
+			// SAFETY: the scope is inside a pinned `ReusableScopedRef`, so it can never move
+			unsafe { self.scope.new_ref_unchecked() }
+		}
+		#[cfg(feature = "no-pin")]
+		{ self.scope.new_ref() }
+	}
+	
+	/// Blocks until all guards of this generation have been dropped (is async on async runtimes). See [ScopedRef::await_guards()]
+	#[cfg(feature = "runtime-none")]
+	#[inline]
+	pub fn await_guards(&self, timeout: Option<Duration>) -> GuardPanics {
+		self.scope.await_guards(timeout)
+	}
+	/// Waits until all guards of this generation have been dropped. See [ScopedRef::await_guards()]
+	#[cfg(feature = "runtime-tokio")]
+	#[inline]
+	pub async fn await_guards(&self, timeout: Option<Duration>) -> GuardPanics {
+		self.scope.await_guards(timeout).await
+	}
+	
+	/// Returns whether there are still living guards that would cause dropping this binding to block
+	#[inline]
+	pub fn has_active_guards(&self) -> bool {
+		self.scope.has_active_guards()
+	}
+	
+	/// Returns the generation of this binding (the first binding is generation 1)
+	#[inline]
+	pub fn generation(&self) -> u64 {
+		self.generation
+	}
+	
+}
+
+// When a binding is dropped, its guards must be handled the same way as when a `ScopedRef` is dropped, since the borrow of its data ends here
+impl<'b, ConnectorType: TypeConnector> Drop for ScopedRefBinding<'b, ConnectorType> {
+	fn drop(&mut self) {
+		self.scope.apply_drop_policy();
+		self.scope.header.end_generation();
+	}
+}
@@ -0,0 +1,295 @@
+// Everything that a `ScopedRef` shares with its guards. Guards (and `ScopedKeepAlive`s) only store a pointer to this, which makes them pointer-sized
+
+use crate::*;
+use std::{cell::UnsafeCell, mem::{MaybeUninit, size_of}, ptr::NonNull, sync::atomic::{AtomicU64, Ordering}};
+
+#[cfg(not(feature = "sharded-counter"))]
+use std::sync::{Mutex, PoisonError};
+#[cfg(feature = "runtime-none")]
+use std::{thread, time::Instant};
+#[cfg(all(not(feature = "sharded-counter"), feature = "runtime-none" ))]
+use std::thread::Thread;
+#[cfg(all(not(feature = "sharded-counter"), feature = "runtime-tokio"))]
+use {std::sync::Arc, tokio::sync::Notify};
+#[cfg(feature = "sharded-counter")]
+use std::time::Duration;
+
+
+
+const GENERATION_ENDED: u64 = 1 << 63;
+
+pub(crate) struct ScopeHeader {
+	
+	// this is the scope's `NonNull<ConnectorType::Super<'static>>`, which may be a wide pointer. The connector is erased so that `ScopedKeepAlive` and `AnyScopedRefGuard` can point to any header
+	data_ptr: UnsafeCell<MaybeUninit<[*const (); 2]>>,
+	
+	pub(crate) counter: GuardCounter,
+	// the sharded counter is polled by waiters instead (see `Waiting::wait_until()`)
+	#[cfg(not(feature = "sharded-counter"))]
+	waiters: Waiters,
+	pub(crate) panics: PanicRecord,
+	pub(crate) hooks: ScopeHooks,
+	
+	// the generation of the `ReusableScopedRef` binding that the data belongs to, which has `GENERATION_ENDED` set once that binding is dropped (this is always 0 for other scopes). Guards check this in debug builds, so a guard that outlives its binding is caught before it reads the next generation's data
+	generation: AtomicU64,
+	
+}
+
+// SAFETY: the data pointer is only written while there are no active guards (see `set_data_ptr()`), and it is only read by guards, which are only `Send` / `Sync` when the data is
+unsafe impl Send for ScopeHeader {}
+unsafe impl Sync for ScopeHeader {}
+
+impl ScopeHeader {
+	
+	pub(crate) fn new<ConnectorType: TypeConnector>(data_ptr: NonNull<ConnectorType::Super<'static>>) -> Self {
+		Self {
+			data_ptr: UnsafeCell::new(Self::erase::<ConnectorType>(data_ptr)),
+			counter: GuardCounter::new(),
+			#[cfg(not(feature = "sharded-counter"))]
+			waiters: Waiters::new(),
+			panics: PanicRecord::new(),
+			hooks: ScopeHooks::new(),
+			generation: AtomicU64::new(0),
+		}
+	}
+	
+	fn erase<ConnectorType: TypeConnector>(data_ptr: NonNull<ConnectorType::Super<'static>>) -> MaybeUninit<[*const (); 2]> {
+		const { assert!(size_of::<NonNull<ConnectorType::Super<'static>>>() <= size_of::<[*const (); 2]>(), "pointers are expected to be at most two words") };
+		let mut erased_ptr = MaybeUninit::<[*const (); 2]>::uninit();
+		unsafe {
+			// SAFETY: the size is checked above, and the alignment of every pointer is the alignment of `*const ()`
+			erased_ptr.as_mut_ptr().cast::<NonNull<ConnectorType::Super<'static>>>().write(data_ptr);
+		}
+		erased_ptr
+	}
+	
+	// SAFETY: `ConnectorType` must be the connector that the header was created with
+	#[inline]
+	pub(crate) unsafe fn data_ptr<ConnectorType: TypeConnector>(&self) -> NonNull<ConnectorType::Super<'static>> {
+		#[cfg(debug_assertions)]
+		self.check_generation();
+		unsafe { (*self.data_ptr.get()).as_ptr().cast::<NonNull<ConnectorType::Super<'static>>>().read() }
+	}
+	
+	// SAFETY: `ConnectorType` must be the connector that the header was created with, and there must not be any active guards (or anything else that could read the data pointer)
+	pub(crate) unsafe fn set_data_ptr<ConnectorType: TypeConnector>(&self, data_ptr: NonNull<ConnectorType::Super<'static>>) {
+		unsafe { *self.data_ptr.get() = Self::erase::<ConnectorType>(data_ptr); }
+	}
+	
+	// called by `ReusableScopedRef` when a binding starts and ends
+	#[inline]
+	pub(crate) fn set_generation(&self, generation: u64) {
+		self.generation.store(generation, Ordering::Relaxed);
+	}
+	#[inline]
+	pub(crate) fn end_generation(&self) {
+		self.generation.fetch_or(GENERATION_ENDED, Ordering::Relaxed);
+	}
+	
+	// this is only checked in debug builds, since a guard can only outlive its binding with the "unsafe-drop-does-nothing" or "unsafe-drop-does-panic" features
+	#[cfg(debug_assertions)]
+	#[inline]
+	pub(crate) fn check_generation(&self) {
+		let generation = self.generation.load(Ordering::Relaxed);
+		assert!(generation & GENERATION_ENDED == 0, "A guard from generation {} of a `ReusableScopedRef` was used after its binding was dropped", generation & !GENERATION_ENDED);
+	}
+	
+	// Releases one guard. NOTE: the `ScopedRef` might be dropped as soon as the counter reaches zero, so this never touches the header after the guard is released. Anything that has to be woken is copied out of the header first
+	#[cfg(not(feature = "sharded-counter"))]
+	pub(crate) fn release_guard(&self) {
+		let mut state = self.counter.state();
+		let mut called_idle = false;
+		loop {
+			if state.count() == 1 && !called_idle && self.hooks.on_idle.get().is_some() {
+				// this is most likely the last guard, and the hook has to run before the guard is released
+				self.hooks.idle();
+				called_idle = true;
+				state = self.counter.state();
+				continue;
+			}
+			if state.count() > 1 && called_idle {
+				// a guard was created while `on_idle` ran, so the scope is busy again
+				self.hooks.busy();
+				called_idle = false;
+			}
+			let to_wake = state.has_waiters().then(|| self.waiters.to_wake());
+			match self.counter.try_decrement(state) {
+				Ok(()) => {
+					if let Some(to_wake) = to_wake { Waiters::wake(to_wake); }
+					return;
+				}
+				Err(new_state) => state = new_state,
+			}
+		}
+	}
+	#[cfg(feature = "sharded-counter")]
+	#[inline]
+	pub(crate) fn release_guard(&self) {
+		self.counter.decrement();
+	}
+	
+	// registers the current thread (or task) as waiting for the counter to change, which has to be done before the counter is checked
+	#[inline]
+	pub(crate) fn waiting(&self) -> Waiting<'_> {
+		#[cfg(not(feature = "sharded-counter"))]
+		self.waiters.register(&self.counter);
+		Waiting { header: self }
+	}
+	
+}
+
+
+
+// The threads (or tasks) that are waiting for guards to be dropped or admitted. Guards only look at this when the counter says that there are waiters, so dropping a guard usually only costs a single atomic operation
+#[cfg(all(not(feature = "sharded-counter"), feature = "runtime-none" ))]
+struct Waiters {
+	threads: Mutex<Vec<Thread>>,
+}
+#[cfg(all(not(feature = "sharded-counter"), feature = "runtime-tokio"))]
+struct Waiters {
+	count: Mutex<usize>,
+	// this is in an `Arc` so that it can still be notified after the guard is released
+	notify: Arc<Notify>,
+}
+
+#[cfg(all(not(feature = "sharded-counter"), feature = "runtime-none" ))]
+impl Waiters {
+	
+	fn new() -> Self {
+		Self { threads: Mutex::new(Vec::new()) }
+	}
+	
+	// the lock is never held while anything can panic, so poisoning can be ignored
+	fn register(&self, counter: &GuardCounter) {
+		let mut threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+		threads.push(thread::current());
+		counter.set_has_waiters(true);
+	}
+	
+	fn unregister(&self, counter: &GuardCounter) {
+		let mut threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+		let current_id = thread::current().id();
+		if let Some(index) = threads.iter().position(|thread| thread.id() == current_id) {
+			threads.swap_remove(index);
+		}
+		if threads.is_empty() { counter.set_has_waiters(false); }
+	}
+	
+	fn to_wake(&self) -> Vec<Thread> {
+		self.threads.lock().unwrap_or_else(PoisonError::into_inner).clone()
+	}
+	
+	fn wake(threads: Vec<Thread>) {
+		for thread in threads {
+			thread.unpark();
+		}
+	}
+	
+}
+
+#[cfg(all(not(feature = "sharded-counter"), feature = "runtime-tokio"))]
+impl Waiters {
+	
+	fn new() -> Self {
+		Self {
+			count: Mutex::new(0),
+			notify: Arc::new(Notify::new()),
+		}
+	}
+	
+	// the lock is never held while anything can panic, so poisoning can be ignored
+	fn register(&self, counter: &GuardCounter) {
+		let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+		*count += 1;
+		counter.set_has_waiters(true);
+	}
+	
+	fn unregister(&self, counter: &GuardCounter) {
+		let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+		*count -= 1;
+		if *count == 0 { counter.set_has_waiters(false); }
+	}
+	
+	fn to_wake(&self) -> Arc<Notify> {
+		self.notify.clone()
+	}
+	
+	fn wake(notify: Arc<Notify>) {
+		notify.notify_waiters();
+	}
+	
+}
+
+
+
+// Keeps the current thread (or task) registered as a waiter for as long as this exists, which also handles futures that are cancelled
+pub(crate) struct Waiting<'a> {
+	#[cfg_attr(feature = "sharded-counter", allow(dead_code))] // nothing has to be unregistered with the sharded counter
+	header: &'a ScopeHeader,
+}
+
+// with the sharded counter, guards can't tell when the count reaches zero, so waiters check it repeatedly (with an increasing delay) instead of being woken
+#[cfg(feature = "sharded-counter")]
+const MIN_POLL_DELAY: Duration = Duration::from_micros(10);
+#[cfg(feature = "sharded-counter")]
+const MAX_POLL_DELAY: Duration = Duration::from_millis(1);
+
+impl Waiting<'_> {
+	
+	// Blocks until the condition is true or the deadline has passed, and returns whether the condition became true. The condition is checked every time a guard wakes this thread
+	#[cfg(feature = "runtime-none")]
+	pub(crate) fn wait_until(&self, deadline: Option<Instant>, mut condition: impl FnMut() -> bool) -> bool {
+		#[cfg(feature = "sharded-counter")]
+		let mut poll_delay = MIN_POLL_DELAY;
+		loop {
+			if condition() { return true; }
+			let timeout = match deadline {
+				Some(deadline) => {
+					let now = Instant::now();
+					if now >= deadline { return false; }
+					Some(deadline - now)
+				}
+				None => None,
+			};
+			// a guard that is dropped between the check and this still unparks this thread, since parking returns immediately after an unpark
+			#[cfg(not(feature = "sharded-counter"))]
+			match timeout {
+				Some(timeout) => thread::park_timeout(timeout),
+				None => thread::park(),
+			}
+			#[cfg(feature = "sharded-counter")]
+			{
+				thread::park_timeout(timeout.map_or(poll_delay, |timeout| timeout.min(poll_delay)));
+				poll_delay = (poll_delay * 2).min(MAX_POLL_DELAY);
+			}
+		}
+	}
+	
+	// Waits until the condition is true. The condition is checked every time a guard notifies this task
+	#[cfg(feature = "runtime-tokio")]
+	pub(crate) async fn wait_until(&self, mut condition: impl FnMut() -> bool) {
+		#[cfg(feature = "sharded-counter")]
+		let mut poll_delay = MIN_POLL_DELAY;
+		loop {
+			// creating the notification before checking the condition ensures that none are missed
+			#[cfg(not(feature = "sharded-counter"))]
+			let notified = self.header.waiters.notify.notified();
+			if condition() { return; }
+			#[cfg(not(feature = "sharded-counter"))]
+			notified.await;
+			#[cfg(feature = "sharded-counter")]
+			{
+				tokio::time::sleep(poll_delay).await;
+				poll_delay = (poll_delay * 2).min(MAX_POLL_DELAY);
+			}
+		}
+	}
+	
+}
+
+#[cfg(not(feature = "sharded-counter"))]
+impl Drop for Waiting<'_> {
+	fn drop(&mut self) {
+		self.header.waiters.unregister(&self.header.counter);
+	}
+}
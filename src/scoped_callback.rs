@@ -0,0 +1,87 @@
+use crate::*;
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+/// A `'static` handle to a non-`'static` closure, which can be stored in things like event buses or logging hooks. To create this type, use [ScopedRef::new_callback()] (on a `ScopedRef` with the [DynFn] connector)
+/// 
+/// This holds a [ScopedRefGuard], so the `ScopedRef` that it came from cannot be dropped until every clone of this callback is dropped. Because of that, removing a callback from wherever it was registered is what allows the scope to end
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// let registry: Arc<Mutex<Vec<ScopedCallback<u32, ()>>>> = Arc::default();
+/// let total = AtomicU32::new(0);
+/// {
+///     let add = |amount: u32| { total.fetch_add(amount, Ordering::Relaxed); };
+///     make_scoped_ref!(scoped_add = (&add) as DynFn<u32, ()>);
+///     registry.lock().unwrap().push(scoped_add.new_callback());
+///     // ... give `registry` to something that calls the callbacks and eventually removes them
+/// } // this blocks until every callback is removed from the registry
+/// ```
+pub struct ScopedCallback<Args: 'static, R: 'static> {
+	pub(crate) data_ref: ScopedRefGuard<DynFn<Args, R>>,
+}
+
+impl<Args: 'static, R: 'static> ScopedCallback<Args, R> {
+	
+	/// Creates a new callback from a guard
+	#[inline]
+	pub fn new(data_ref: ScopedRefGuard<DynFn<Args, R>>) -> Self {
+		Self { data_ref }
+	}
+	
+	/// Calls the inner closure. Functions with multiple arguments can use a tuple for `Args`
+	#[inline]
+	pub fn call(&self, args: Args) -> R {
+		(self.data_ref.inner())(args)
+	}
+	
+	/// Returns the guard that this callback holds
+	#[inline]
+	pub fn into_guard(self) -> ScopedRefGuard<DynFn<Args, R>> {
+		self.data_ref
+	}
+	
+}
+
+impl<Args: 'static, R: 'static> From<ScopedRefGuard<DynFn<Args, R>>> for ScopedCallback<Args, R> {
+	#[inline]
+	fn from(data_ref: ScopedRefGuard<DynFn<Args, R>>) -> Self {
+		Self::new(data_ref)
+	}
+}
+
+impl<Args: 'static, R: 'static> Clone for ScopedCallback<Args, R> {
+	#[inline]
+	fn clone(&self) -> Self {
+		Self { data_ref: self.data_ref.clone() }
+	}
+}
+
+impl<Args: 'static, R: 'static> std::fmt::Debug for ScopedCallback<Args, R> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ScopedCallback").finish_non_exhaustive()
+	}
+}
+
+
+
+impl<'a, Args: 'static, R: 'static> ScopedRef<'a, DynFn<Args, R>> {
+	
+	/// Returns a new [ScopedCallback], which can be used to call the referenced closure as if it is `'static`
+	#[cfg(not(feature = "no-pin"))]
+	#[inline]
+	pub fn new_callback(self: &Pin<&mut Self>) -> ScopedCallback<Args, R> {
+		ScopedCallback::new(self.new_ref())
+	}
+	/// Returns a new [ScopedCallback], which can be used to call the referenced closure as if it is `'static`
+	#[cfg(feature = "no-pin")]
+	#[inline]
+	pub fn new_callback(&self) -> ScopedCallback<Args, R> {
+		ScopedCallback::new(self.new_ref())
+	}
+	
+}
@@ -0,0 +1,201 @@
+use crate::*;
+use std::any::Any;
+
+#[cfg(feature = "runtime-none")]
+use std::{thread, panic::{self, AssertUnwindSafe}, sync::mpsc::{self, Sender, Receiver}};
+#[cfg(feature = "runtime-tokio")]
+use tokio::{runtime::Handle, task::{JoinSet, JoinError, AbortHandle}};
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+/// A group of threads (or tasks on async runtimes) that each hold a guard from the same [ScopedRef]. To create this type, use [ScopedRef::join_set()]
+/// 
+/// Because this borrows the `ScopedRef`, it is always dropped before the `ScopedRef` is. When it is dropped, it waits for every thread / task that hasn't been joined yet. Panics from those aren't resumed (that would drop the `ScopedRef` while unwinding), but each thread / task drops its guard while it unwinds, so they are reported by [ScopedRef::guard_panics()] and [ScopedRef::await_guards()] (along with the message of the first one). To get the first panic back instead, use [ScopedJoinSet::close()], and to resume it, use [ScopedJoinSet::join_all()]
+pub struct ScopedJoinSet<'s, 'a, ConnectorType: TypeConnector, T: Send + 'static> {
+	
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) scope: &'s Pin<&'s mut ScopedRef<'a, ConnectorType>>,
+	#[cfg(feature = "no-pin")]
+	pub(crate) scope: &'s ScopedRef<'a, ConnectorType>,
+	
+	#[cfg(feature = "runtime-none")]
+	pub(crate) results: (Sender<thread::Result<T>>, Receiver<thread::Result<T>>),
+	#[cfg(feature = "runtime-none")]
+	pub(crate) running: usize,
+	#[cfg(feature = "runtime-tokio")]
+	pub(crate) join_set: JoinSet<T>,
+	
+}
+
+impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> {
+	
+	/// Creates a new [ScopedJoinSet] which spawns threads / tasks that each get their own guard from this `ScopedRef`
+	#[cfg(not(feature = "no-pin"))]
+	pub fn join_set<'s, T: Send + 'static>(self: &'s Pin<&mut Self>) -> ScopedJoinSet<'s, 'a, ConnectorType, T> {
+		ScopedJoinSet::new(self)
+	}
+	/// Creates a new [ScopedJoinSet] which spawns threads / tasks that each get their own guard from this `ScopedRef`
+	#[cfg(feature = "no-pin")]
+	pub fn join_set<T: Send + 'static>(&self) -> ScopedJoinSet<'_, 'a, ConnectorType, T> {
+		ScopedJoinSet::new(self)
+	}
+	
+}
+
+impl<'s, 'a, ConnectorType: TypeConnector, T: Send + 'static> ScopedJoinSet<'s, 'a, ConnectorType, T> {
+	
+	#[cfg(not(feature = "no-pin"))]
+	fn new(scope: &'s Pin<&'s mut ScopedRef<'a, ConnectorType>>) -> Self {
+		Self {
+			scope,
+			#[cfg(feature = "runtime-none")]
+			results: mpsc::channel(),
+			#[cfg(feature = "runtime-none")]
+			running: 0,
+			#[cfg(feature = "runtime-tokio")]
+			join_set: JoinSet::new(),
+		}
+	}
+	#[cfg(feature = "no-pin")]
+	fn new(scope: &'s ScopedRef<'a, ConnectorType>) -> Self {
+		Self {
+			scope,
+			#[cfg(feature = "runtime-none")]
+			results: mpsc::channel(),
+			#[cfg(feature = "runtime-none")]
+			running: 0,
+			#[cfg(feature = "runtime-tokio")]
+			join_set: JoinSet::new(),
+		}
+	}
+	
+	/// Spawns a new thread which is given its own guard. The output of the thread can be retrieved with [ScopedJoinSet::join_next()]
+	#[cfg(feature = "runtime-none")]
+	pub fn spawn(&mut self, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> T + Send + 'static) where ScopedRefGuard<ConnectorType>: Send {
+		let data_ref = self.scope.new_ref();
+		let sender = self.results.0.clone();
+		self.running += 1;
+		thread::spawn(move || {
+			// the guard is dropped inside `f`, so it is always released before the result is sent
+			let output = panic::catch_unwind(AssertUnwindSafe(move || f(data_ref)));
+			let _ = sender.send(output);
+		});
+	}
+	/// Spawns a new task which is given its own guard. The output of the task can be retrieved with [ScopedJoinSet::join_next()]
+	#[cfg(feature = "runtime-tokio")]
+	pub fn spawn<Fut: Future<Output = T> + Send + 'static>(&mut self, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> Fut) -> AbortHandle {
+		let data_ref = self.scope.new_ref();
+		self.join_set.spawn(f(data_ref))
+	}
+	/// Spawns a new blocking task (using `tokio::task::spawn_blocking()`) which is given its own guard. The output of the task can be retrieved with [ScopedJoinSet::join_next()]
+	#[cfg(feature = "runtime-tokio")]
+	pub fn spawn_blocking(&mut self, f: impl FnOnce(ScopedRefGuard<ConnectorType>) -> T + Send + 'static) -> AbortHandle where ScopedRefGuard<ConnectorType>: Send {
+		let data_ref = self.scope.new_ref();
+		self.join_set.spawn_blocking(move || f(data_ref))
+	}
+	
+	/// Waits for the next thread to finish and returns its output (or its panic payload), in the order that the threads finish. Returns `None` if there are no threads left (is async on async runtimes)
+	#[cfg(feature = "runtime-none")]
+	pub fn join_next(&mut self) -> Option<thread::Result<T>> {
+		if self.running == 0 { return None; }
+		let output = self.results.1.recv().expect("failed to receive output of scoped thread"); // the set always holds a sender, so this can't fail
+		self.running -= 1;
+		Some(output)
+	}
+	/// Waits for the next task to finish and returns its output (or its error), in the order that the tasks finish. Returns `None` if there are no tasks left
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+		self.join_set.join_next().await
+	}
+	
+	/// Waits for every thread to finish and returns their outputs in the order that they finished. If any thread panicked, this resumes that panic (is async on async runtimes)
+	#[cfg(feature = "runtime-none")]
+	pub fn join_all(mut self) -> Vec<T> {
+		let mut outputs = Vec::with_capacity(self.running);
+		while let Some(output) = self.join_next() {
+			match output {
+				Ok(output) => outputs.push(output),
+				Err(payload) => panic::resume_unwind(payload),
+			}
+		}
+		outputs
+	}
+	/// Waits for every task to finish and returns their outputs in the order that they finished. If any task panicked, this resumes that panic
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn join_all(mut self) -> Vec<T> {
+		let mut outputs = Vec::with_capacity(self.join_set.len());
+		while let Some(output) = self.join_next().await {
+			match output {
+				Ok(output) => outputs.push(output),
+				Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+				Err(err) => panic!("scoped task failed to complete: {err}"),
+			}
+		}
+		outputs
+	}
+	
+	/// Waits for every thread that hasn't been joined yet and returns the payload of the first one that panicked, discarding their outputs (is async on async runtimes)
+	/// 
+	/// This is the same as dropping the set, except that the panic is given back instead of only being reported to the `ScopedRef`
+	#[cfg(feature = "runtime-none")]
+	pub fn close(mut self) -> Option<Box<dyn Any + Send>> {
+		self.drain()
+	}
+	/// Waits for every task that hasn't been joined yet and returns the payload of the first one that panicked, discarding their outputs
+	/// 
+	/// This is the same as dropping the set, except that the panic is given back instead of only being reported to the `ScopedRef`
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn close(mut self) -> Option<Box<dyn Any + Send>> {
+		self.drain().await
+	}
+	
+	// joins every remaining thread / task and keeps the payload of the first one that panicked
+	#[cfg(feature = "runtime-none")]
+	fn drain(&mut self) -> Option<Box<dyn Any + Send>> {
+		let mut first_panic = None;
+		while let Some(output) = self.join_next() {
+			if let Err(payload) = output && first_panic.is_none() { first_panic = Some(payload); }
+		}
+		first_panic
+	}
+	#[cfg(feature = "runtime-tokio")]
+	async fn drain(&mut self) -> Option<Box<dyn Any + Send>> {
+		let mut first_panic = None;
+		while let Some(output) = self.join_next().await {
+			if let Err(err) = output && err.is_panic() && first_panic.is_none() { first_panic = Some(err.into_panic()); }
+		}
+		first_panic
+	}
+	
+	/// Returns the number of threads / tasks that haven't been joined yet
+	#[inline]
+	pub fn len(&self) -> usize {
+		#[cfg(feature = "runtime-none")]
+		{ self.running }
+		#[cfg(feature = "runtime-tokio")]
+		{ self.join_set.len() }
+	}
+	
+	/// Returns whether there are no threads / tasks that haven't been joined yet
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+	
+}
+
+// When `ScopedJoinSet` is dropped, it drains all remaining threads / tasks (which always happens before the `ScopedRef` is dropped). Panics are left to the `ScopedRef`'s panic record, since panicking here would drop the `ScopedRef` while unwinding
+impl<'s, 'a, ConnectorType: TypeConnector, T: Send + 'static> Drop for ScopedJoinSet<'s, 'a, ConnectorType, T> {
+	fn drop(&mut self) {
+		if self.is_empty() { return; }
+		#[cfg(feature = "runtime-none")]
+		let first_panic = self.drain();
+		#[cfg(feature = "runtime-tokio")]
+		let first_panic = tokio::task::block_in_place(|| Handle::current().block_on(self.drain()));
+		// the guards of the panicked threads / tasks were already counted, but the payload also has the message (even if `capture_panic_messages()` wasn't called)
+		if let Some(payload) = first_panic { self.scope.header.panics.record_payload(&*payload); }
+	}
+}
@@ -0,0 +1,194 @@
+use crate::*;
+use std::{marker::PhantomData, mem::ManuallyDrop, ptr::NonNull};
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+/// Creates a new [ScopedOwned] and assigns it to a variable. This uses the format `make_scoped_owned!(scope_var_name = (value) as ConnectorType);`
+/// 
+/// Like [make_scoped_ref], this makes sure that the `ScopedOwned` can't be forgotten (which would allow guards to outlive the data borrowed by the value). Because of that, the variable is a `&mut ScopedOwned`, so the value is taken back with [ScopedOwned::take_inner()] instead of [ScopedOwned::into_inner()]
+#[macro_export]
+macro_rules! make_scoped_owned {
+	($scope:ident = ($input:expr) as $connector:ty) => {
+		let $scope = $input;
+		let $scope = &mut unsafe {
+			$crate::ScopedOwned::<$connector>::new($scope)
+		};
+	};
+}
+
+
+
+/// Similar to [ScopedRef], but this owns the value that it gives out (which can still contain non-`'static` references). To create this type, the [make_scoped_owned] macro is recommended
+/// 
+/// The value can be taken back with [ScopedOwned::take_inner()], which waits until every guard is dropped ([ScopedOwned::into_inner()] does the same for a `ScopedOwned` that is owned directly, like one created by [ScopedOwned::new_static()]). When this is dropped, it follows the same drop policy as `ScopedRef`, unless [ScopedOwned::with_deferred_drop()] was used
+pub struct ScopedOwned<'a, ConnectorType: TypeConnector> {
+	pub(crate) inner: Option<ScopedOwnedInner<'a, ConnectorType>>,
+	pub(crate) defer_drop: bool,
+}
+
+// the value and the scope that references it, which are both heap-allocated so that `ScopedOwned` can be moved
+pub(crate) struct ScopedOwnedInner<'a, ConnectorType: TypeConnector> {
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) scope: ManuallyDrop<Pin<Box<ScopedRef<'a, ConnectorType>>>>,
+	#[cfg(feature = "no-pin")]
+	pub(crate) scope: ManuallyDrop<ScopedRef<'a, ConnectorType>>,
+	pub(crate) data: NonNull<ConnectorType::Super<'a>>, // created with `Box::into_raw()`
+	pub(crate) phantom: PhantomData<Box<ConnectorType::Super<'a>>>,
+}
+
+// SAFETY: this owns the value (so it needs `Send`) and gives out references to it (so it needs `Sync`)
+unsafe impl<'a, ConnectorType: TypeConnector> Send for ScopedOwnedInner<'a, ConnectorType> where ConnectorType::Super<'a>: Send + Sync {}
+unsafe impl<'a, ConnectorType: TypeConnector> Sync for ScopedOwnedInner<'a, ConnectorType> where ConnectorType::Super<'a>: Send + Sync {}
+
+impl<'a, ConnectorType: TypeConnector> ScopedOwnedInner<'a, ConnectorType> {
+	
+	// drops the scope (following the drop policy) and returns the value
+	fn into_data(self) -> ConnectorType::Super<'a> where ConnectorType::Super<'a>: Sized {
+		let mut this = ManuallyDrop::new(self);
+		unsafe {
+			ManuallyDrop::drop(&mut this.scope);
+			*Box::from_raw(this.data.as_ptr())
+		}
+	}
+	
+}
+
+// When the inner data is dropped, the scope must be dropped (which waits for the guards) before the value is
+impl<'a, ConnectorType: TypeConnector> Drop for ScopedOwnedInner<'a, ConnectorType> {
+	fn drop(&mut self) {
+		unsafe {
+			ManuallyDrop::drop(&mut self.scope);
+			drop(Box::from_raw(self.data.as_ptr()));
+		}
+	}
+}
+
+impl<'a, ConnectorType: TypeConnector> ScopedOwned<'a, ConnectorType> {
+	
+	/// NOTE: `ScopedOwned` is meant to be created using the [make_scoped_owned] macro (or [ScopedOwned::new_static()] if the value is `'static`)
+	/// 
+	/// Creates a new `ScopedOwned` which owns the given value
+	/// 
+	/// # Safety
+	/// 
+	/// This function is considered unsafe because it is possible to create dangling pointers with this if you 1: create a `ScopedOwned` with a value that borrows other data, 2: create a `ScopedRefGuard` with it, 3: use `std::mem::forget()` to drop the `ScopedOwned`, and 4: drop the data that the value borrowed
+	pub unsafe fn new(value: ConnectorType::Super<'a>) -> Self where ConnectorType::Super<'a>: Sized {
+		let data = NonNull::from(Box::leak(Box::new(value)));
+		// SAFETY: the value is only dropped after the scope is dropped
+		let scope = unsafe { ScopedRef::new(data.as_ref()) };
+		Self {
+			inner: Some(ScopedOwnedInner {
+				#[cfg(not(feature = "no-pin"))]
+				scope: ManuallyDrop::new(Box::pin(scope)),
+				#[cfg(feature = "no-pin")]
+				scope: ManuallyDrop::new(scope),
+				data,
+				phantom: PhantomData,
+			}),
+			defer_drop: false,
+		}
+	}
+	
+	/// Returns a new guard that can be used to access the value as if it is `'static`. This panics if the value was already taken
+	#[inline]
+	pub fn new_ref(&self) -> ScopedRefGuard<ConnectorType> {
+		let scope = &self.inner().scope;
+		#[cfg(not(feature = "no-pin"))]
+		{
+			// SAFETY: the scope is boxed and pinned, so it can never move
+			unsafe { scope.new_ref_unchecked() }
+		}
+		#[cfg(feature = "no-pin")]
+		{ scope.new_ref() }
+	}
+	
+	/// Blocks until every guard is dropped, then takes the value out of this `ScopedOwned` (is async on async runtimes). Returns `None` if the value was already taken
+	#[cfg(feature = "runtime-none")]
+	pub fn take_inner(&mut self) -> Option<ConnectorType::Super<'a>> where ConnectorType::Super<'a>: Sized {
+		self.inner.as_ref()?.scope.await_guards(None);
+		self.inner.take().map(ScopedOwnedInner::into_data)
+	}
+	/// Waits until every guard is dropped, then takes the value out of this `ScopedOwned`. Returns `None` if the value was already taken
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn take_inner(&mut self) -> Option<ConnectorType::Super<'a>> where ConnectorType::Super<'a>: Sized {
+		self.inner.as_ref()?.scope.await_guards(None).await;
+		self.inner.take().map(ScopedOwnedInner::into_data)
+	}
+	
+	/// Blocks until every guard is dropped, then returns the value (is async on async runtimes). This panics if the value was already taken
+	#[cfg(feature = "runtime-none")]
+	pub fn into_inner(mut self) -> ConnectorType::Super<'a> where ConnectorType::Super<'a>: Sized {
+		self.take_inner().expect("the value of this `ScopedOwned` was already taken")
+	}
+	/// Waits until every guard is dropped, then returns the value. This panics if the value was already taken
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn into_inner(mut self) -> ConnectorType::Super<'a> where ConnectorType::Super<'a>: Sized {
+		self.take_inner().await.expect("the value of this `ScopedOwned` was already taken")
+	}
+	
+	/// Returns information about the guards that were dropped during a panic so far (see [GuardPanics])
+	#[inline]
+	pub fn guard_panics(&self) -> GuardPanics {
+		self.inner().scope.guard_panics()
+	}
+	
+	/// Returns whether there are still living `ScopedRefGuard`s that would cause dropping this `ScopedOwned` to block
+	#[inline]
+	pub fn has_active_guards(&self) -> bool {
+		self.inner.as_ref().is_some_and(|inner| inner.scope.has_active_guards())
+	}
+	
+	/// Returns whether the value was already taken
+	#[inline]
+	pub fn is_taken(&self) -> bool {
+		self.inner.is_none()
+	}
+	
+	#[inline]
+	fn inner(&self) -> &ScopedOwnedInner<'a, ConnectorType> {
+		self.inner.as_ref().expect("the value of this `ScopedOwned` was already taken")
+	}
+	
+}
+
+impl<ConnectorType: TypeConnector> ScopedOwned<'static, ConnectorType> {
+	
+	/// Creates a new `ScopedOwned` which owns the given `'static` value. Unlike [ScopedOwned::new()], this is safe because forgetting the `ScopedOwned` can only leak the value
+	pub fn new_static(value: ConnectorType::Super<'static>) -> Self where ConnectorType::Super<'static>: Sized {
+		unsafe { Self::new(value) }
+	}
+	
+	/// Makes this `ScopedOwned` drop its value on a background thread (which is a blocking task on async runtimes) if there are still active guards when it is dropped, so that dropping it never blocks. This is only available for `'static` values, since the value can outlive the current scope
+	#[inline]
+	pub fn with_deferred_drop(mut self) -> Self where ConnectorType::Super<'static>: Send + Sync {
+		self.defer_drop = true;
+		self
+	}
+	
+}
+
+// When `ScopedOwned` is dropped, it drops the scope (following the drop policy) and then the value, unless the drop is deferred
+impl<'a, ConnectorType: TypeConnector> Drop for ScopedOwned<'a, ConnectorType> {
+	fn drop(&mut self) {
+		if !self.defer_drop { return; }
+		let Some(inner) = self.inner.take_if(|inner| inner.scope.has_active_guards()) else { return; };
+		// SAFETY: `defer_drop` can only be set by `with_deferred_drop()`, which requires `'a` to be `'static` and the value to be `Send + Sync`
+		let inner = unsafe { std::mem::transmute::<ScopedOwnedInner<'a, ConnectorType>, ScopedOwnedInner<'static, ConnectorType>>(inner) };
+		let inner = AssertSend(inner);
+		#[cfg(feature = "runtime-none")]
+		std::thread::spawn(move || drop(inner));
+		#[cfg(feature = "runtime-tokio")]
+		match tokio::runtime::Handle::try_current() {
+			Ok(handle) => drop(handle.spawn_blocking(move || drop(inner))),
+			// this can be dropped outside of any runtime, in which case there's no blocking pool to use
+			Err(_) => drop(std::thread::spawn(move || drop(inner))),
+		}
+	}
+}
+
+// `ScopedOwnedInner` is only `Send` when the value is `Send + Sync`, which can't be checked in `Drop`
+struct AssertSend<T>(T);
+unsafe impl<T> Send for AssertSend<T> {}
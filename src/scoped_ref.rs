@@ -1,30 +1,125 @@
 use crate::*;
-use std::{time::Duration, marker::PhantomData};
+use std::{borrow::Cow, time::Duration, marker::PhantomData, ptr::NonNull, sync::OnceLock};
 
 #[cfg(feature = "runtime-none")]
-use std::{sync::{Mutex, Condvar}, time::Instant};
+use std::time::Instant;
 #[cfg(feature = "runtime-tokio")]
-use tokio::{runtime::Handle, sync::Notify};
+use tokio::runtime::Handle;
 
 #[cfg(not(feature = "no-pin"))]
-use std::{sync::atomic::{Ordering, AtomicU32}, pin::Pin};
+use std::pin::Pin;
 #[cfg(feature = "no-pin")]
 use std::sync::Arc;
 
 
 
+// shared between a `ScopedRef` and its guards, guards call these when the number of active guards changes between zero and one
+pub(crate) struct ScopeHooks {
+	pub(crate) on_idle: OnceLock<Box<dyn Fn() + Send + Sync>>,
+	pub(crate) on_busy: OnceLock<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl ScopeHooks {
+	
+	pub(crate) fn new() -> Self {
+		Self {
+			on_idle: OnceLock::new(),
+			on_busy: OnceLock::new(),
+		}
+	}
+	
+	#[cfg(not(feature = "sharded-counter"))]
+	#[inline]
+	pub(crate) fn idle(&self) {
+		if let Some(on_idle) = self.on_idle.get() { on_idle(); }
+	}
+	
+	#[inline]
+	pub(crate) fn busy(&self) {
+		if let Some(on_busy) = self.on_busy.get() { on_busy(); }
+	}
+	
+	// features are unified across the dependency graph, so hooks that would never be called have to fail loudly instead
+	#[track_caller]
+	#[inline]
+	pub(crate) fn check_supported() {
+		#[cfg(feature = "sharded-counter")]
+		panic!("`on_idle` and `on_busy` can't be used with the \"sharded-counter\" feature, since guards can't know whether they're the last one");
+	}
+	
+}
+
+
+
 /// Creates a new [ScopedRef] and assigns it to a variable. This uses the format `make_scoped_ref!(scope_var_name = (reference_to_scope) as ConnectorType);`
+/// 
+/// The reference is coerced to the connector's type, so (for example) a closure can be given for a [DynFn] connector, and a `&String` can be given for a [Str] connector
+/// 
+/// Multiple references can share a single `ScopedRef` by giving a tuple of references and a tuple of connectors, as in `make_scoped_ref!(scope_var_name = (&a, &b, &c) as (A, B, C));`. Guards from that scope give a tuple of references, so they can be used like `let (a, b, c) = *data_ref.inner();`
+/// 
+/// The `ScopedRef` can also be configured by giving a [ScopedRefBuilder] after the connector, as in `make_scoped_ref!(scope_var_name = (reference_to_scope) as ConnectorType, ScopedRef::builder().name("name"));`
+/// 
+/// The connector can also be left out, as in `make_scoped_ref!(scope_var_name = reference_to_scope);`. In that case, the connector of the referenced type is used if it implements [HasConnector] (which includes `[T]`, `str`, `Path`, `OsStr`, `CStr`, and any type using `#[derive(TypeConnector)]`), and otherwise [Ref] is used (which only works for types without lifetimes)
+/// 
+/// ### Examples:
+/// 
+/// ```ignore
+/// let data = vec!(1u8, 2, 3);
+/// make_scoped_ref!(scoped_data = (&*data) as SliceU8); // `ScopedRef<SliceU8>`
+/// make_scoped_ref!(scoped_data = &*data); // `ScopedRef<Slice<u8>>`
+/// make_scoped_ref!(scoped_data = &data); // `ScopedRef<Ref<Vec<u8>>>`
+/// 
+/// let config = String::from("config");
+/// make_scoped_ref!(scoped_data = (&*data, &*config) as (Slice<u8>, Str)); // `ScopedRef<(Slice<u8>, Str)>`
+/// ```
 #[macro_export]
 macro_rules! make_scoped_ref {
+	($scope:ident = ($first_input:expr, $($input:expr),+ $(,)?) as ($first_connector:ty, $($connector:ty),+ $(,)?)) => {
+		// the tuple of references is stored here so that the `ScopedRef` can reference it
+		let $scope: <($first_connector, $($connector),+) as $crate::TypeConnector>::Super<'_> = ($first_input, $($input),+);
+		$crate::make_scoped_ref!($scope = (&$scope) as ($first_connector, $($connector),+));
+	};
 	($scope:ident = ($input:expr) as $connector:ty) => {
+		// the type annotation allows coercions, like `&String` to `&str` or `&{closure}` to `&dyn Fn(..)`
+		let $scope: &<$connector as $crate::TypeConnector>::Super<'_> = $input;
+		#[cfg(not(feature = "no-pin"))]
+		let $scope = &mut unsafe {
+			let $scope = $crate::ScopedRef::<$connector>::new($scope);
+			std::pin::pin!($scope)
+		};
+		#[cfg(feature = "no-pin")]
+		let $scope = &mut unsafe {
+			$crate::ScopedRef::<$connector>::new($scope)
+		};
+	};
+	($scope:ident = ($input:expr) as $connector:ty, $builder:expr) => {
+		let $scope: &<$connector as $crate::TypeConnector>::Super<'_> = $input;
+		let builder: $crate::ScopedRefBuilder<$connector> = $builder;
 		#[cfg(not(feature = "no-pin"))]
 		let $scope = &mut unsafe {
-			let $scope = $crate::ScopedRef::<$connector>::new($input);
+			let $scope = builder.build($scope);
 			std::pin::pin!($scope)
 		};
 		#[cfg(feature = "no-pin")]
 		let $scope = &mut unsafe {
-			$crate::ScopedRef::<$connector>::new($input)
+			builder.build($scope)
+		};
+	};
+	($scope:ident = $input:expr) => {
+		let $scope = $input;
+		let connector = {
+			#[allow(unused_imports)]
+			use $crate::__connector_inference::{ViaHasConnector as _, ViaRef as _};
+			(&$crate::__connector_inference::Probe::of($scope)).connector()
+		};
+		#[cfg(not(feature = "no-pin"))]
+		let $scope = &mut unsafe {
+			let $scope = $crate::__connector_inference::new_scoped_ref($scope, connector);
+			std::pin::pin!($scope)
+		};
+		#[cfg(feature = "no-pin")]
+		let $scope = &mut unsafe {
+			$crate::__connector_inference::new_scoped_ref($scope, connector)
 		};
 	};
 }
@@ -34,25 +129,30 @@ macro_rules! make_scoped_ref {
 /// Allows you to create runtime-checked scope where a non-`'static` reference can be used as if it is `'static`.
 /// 
 /// This works because the static-friendly guards prevent their parent `ScopeRef` from being dropped, meaning their data can always be accessed as if it is static. The resulting functionality is similar to lifetimes superpowers of `std::thread::scope()`, but available everywhere
-pub struct ScopedRef<'a, ConnectorType: TypeConnector> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+pub struct ScopedRef<'a, ConnectorType: TypeConnector> {
 	
-	pub(crate) data_ptr: [u8; std::mem::size_of::<&ConnectorType::Super<'static>>()],
+	// stores the data pointer, the counter, the notify, the panic record, and the hooks together, so that guards only need a single pointer to this (which is an `Arc` when "no-pin" is used)
+	// NOTE: the counter is also needed when "no-pin" is used, because `Arc::strong_count()` is only decremented after `ScopedRefGuard::drop()` sends its notification
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) header: ScopeHeader,
+	#[cfg(feature = "no-pin")]
+	pub(crate) header: Arc<ScopeHeader>,
 	
-	// stores the counter and the notify together, which allows the `Arc<Notify>` when "no-pin" and "runtime-tokio" are used together
-	#[cfg(all(not(feature = "no-pin"), feature = "runtime-none" ))]
-	pub(crate) counter_notify: (AtomicU32, Mutex<()>, Condvar),
-	#[cfg(all(    feature = "no-pin" , feature = "runtime-none" ))]
-	pub(crate) counter_notify: Arc<(Mutex<()>, Condvar)>,
-	#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
-	pub(crate) counter_notify: (AtomicU32, Notify),
-	#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
-	pub(crate) counter_notify: Arc<Notify>,
+	// the number of guards that `acquire()` allows at once, which doesn't affect `new_ref()`
+	pub(crate) max_guards: GuardCount,
+	// these are only set by `ScopedRefBuilder`
+	pub(crate) name: Option<Cow<'static, str>>,
+	pub(crate) drop_timeout: Option<Duration>,
 	
 	pub(crate) phantom: PhantomData<&'a ConnectorType>,
 	
 }
 
-impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+// SAFETY: a `ScopedRef` only gives out guards (which are only `Send` / `Sync` when the data is), so sharing it is like sharing `&T`
+unsafe impl<'a, ConnectorType: TypeConnector> Send for ScopedRef<'a, ConnectorType> where for<'b> ConnectorType::Super<'b>: Sync {}
+unsafe impl<'a, ConnectorType: TypeConnector> Sync for ScopedRef<'a, ConnectorType> where for<'b> ConnectorType::Super<'b>: Sync {}
+
+impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> {
 	
 	/// NOTE: `ScopedRef` is meant to be created using the [make_scoped_ref] macro.
 	/// 
@@ -66,26 +166,31 @@ impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> where [(); s
 		{
 			Handle::current(); // check whether this is being called within a valid tokio runtime (only checks in debug mode, exists bc the drop fn already needs the handle and seeing the panic in `new()` is probably better than in the drop)
 		}
-		let mut output = Self {
-			data_ptr: [0; _],
-			
-			#[cfg(all(not(feature = "no-pin"), feature = "runtime-none" ))]
-			counter_notify: (AtomicU32::new(0), Mutex::new(()), Condvar::new()),
-			#[cfg(all(    feature = "no-pin" , feature = "runtime-none" ))]
-			counter_notify: Arc::new((Mutex::new(()), Condvar::new())),
-			#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
-			counter_notify: (AtomicU32::new(0), Notify::new()),
-			#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
-			counter_notify: Arc::new(Notify::new()),
-			
+		let data_ptr = Self::erase_lifetime(data.into());
+		Self {
+			#[cfg(not(feature = "no-pin"))]
+			header: ScopeHeader::new::<ConnectorType>(data_ptr),
+			#[cfg(feature = "no-pin")]
+			header: Arc::new(ScopeHeader::new::<ConnectorType>(data_ptr)),
+			max_guards: MAX_GUARD_COUNT,
+			name: None,
+			drop_timeout: None,
 			phantom: PhantomData,
-		};
-		let data_ptr: &'a ConnectorType::Super<'a> = data.into();
+		}
+	}
+	
+	// the lifetime of the reference is erased to `'static` so that it can be stored in the header, and it is only ever turned back into a reference by `ScopedRefGuard::inner()`
+	#[inline]
+	fn erase_lifetime(data: &'a ConnectorType::Super<'a>) -> NonNull<ConnectorType::Super<'static>> {
 		unsafe {
-			// SAFETY: the type for `data_ptr` ensures that it is the same size as `&ConnectorType::Super`
-			*(&mut output.data_ptr as *mut _ as *mut &'a ConnectorType::Super<'a>) = data_ptr;
+			// SAFETY: `Super<'a>` and `Super<'static>` only differ in lifetimes, so they always have the same layout
+			std::mem::transmute::<&'a ConnectorType::Super<'a>, &'static ConnectorType::Super<'static>>(data).into()
 		}
-		output
+	}
+	
+	// SAFETY: there must not be any active guards
+	pub(crate) unsafe fn set_data(&mut self, data: &'a ConnectorType::Super<'a>) {
+		unsafe { self.header.set_data_ptr::<ConnectorType>(Self::erase_lifetime(data)); }
 	}
 	
 	/// Returns a new guard that can be used to access `&T` as if it is `&'static T`
@@ -94,13 +199,21 @@ impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> where [(); s
 	#[cfg(not(feature = "no-pin"))]
 	#[inline]
 	pub fn new_ref(self: &Pin<&mut Self>) -> ScopedRefGuard<ConnectorType> {
-		self.counter_notify.0.fetch_add(1, Ordering::AcqRel);
+		unsafe { self.as_ref().get_ref().new_ref_unchecked() }
+	}
+	// SAFETY: `self` must never be moved again (which `Pin` usually ensures)
+	#[cfg(not(feature = "no-pin"))]
+	#[inline]
+	pub(crate) unsafe fn new_ref_unchecked(&self) -> ScopedRefGuard<ConnectorType> {
+		if self.header.counter.increment() { self.hooks().busy(); }
+		unsafe { self.make_guard() }
+	}
+	// SAFETY: `self` must never be moved again, and the counter must already include the new guard
+	#[cfg(not(feature = "no-pin"))]
+	#[inline]
+	unsafe fn make_guard(&self) -> ScopedRefGuard<ConnectorType> {
 		ScopedRefGuard {
-			data_ptr: self.data_ptr,
-			#[cfg(feature = "runtime-none" )]
-			counter_notify: (unsafe {&*(&self.counter_notify.0 as *const _)}, unsafe {&*(&self.counter_notify.1 as *const _)}, unsafe {&*(&self.counter_notify.2 as *const _)}),
-			#[cfg(feature = "runtime-tokio")]
-			counter_notify: (unsafe {&*(&self.counter_notify.0 as *const _)}, unsafe {&*(&self.counter_notify.1 as *const _)}),
+			keep_alive: ScopedKeepAlive { header: NonNull::from(&self.header) },
 			phantom: PhantomData,
 		}
 	}
@@ -108,81 +221,201 @@ impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> where [(); s
 	#[cfg(feature = "no-pin")]
 	#[inline]
 	pub fn new_ref(&self) -> ScopedRefGuard<ConnectorType> {
+		if self.header.counter.increment() { self.hooks().busy(); }
+		self.make_guard()
+	}
+	// the counter must already include the new guard
+	#[cfg(feature = "no-pin")]
+	#[inline]
+	fn make_guard(&self) -> ScopedRefGuard<ConnectorType> {
 		ScopedRefGuard {
-			data_ptr: self.data_ptr,
-			counter_notify: self.counter_notify.clone(),
+			keep_alive: ScopedKeepAlive { header: self.header.clone() },
 			phantom: PhantomData,
 		}
 	}
 	
+	/// Sets the number of guards that [ScopedRef::acquire()] allows at once, and returns the `ScopedRef`. This is meant to be used before the `ScopedRef` is pinned (see [ScopedRef::set_max_guards()] otherwise)
+	/// 
+	/// Guards created with [ScopedRef::new_ref()] (or by cloning) are not limited, but they are still counted. Limits above [MAX_GUARD_COUNT] are lowered to it
+	#[inline]
+	pub fn with_max_guards(mut self, max_guards: GuardCount) -> Self {
+		self.set_max_guards_inner(max_guards);
+		self
+	}
+	/// Sets the number of guards that [ScopedRef::acquire()] allows at once
+	#[cfg(not(feature = "no-pin"))]
+	#[inline]
+	pub fn set_max_guards(self: &mut Pin<&mut Self>, max_guards: GuardCount) {
+		// SAFETY: this doesn't move the `ScopedRef`
+		unsafe { self.as_mut().get_unchecked_mut() }.set_max_guards_inner(max_guards);
+	}
+	/// Sets the number of guards that [ScopedRef::acquire()] allows at once
+	#[cfg(feature = "no-pin")]
+	#[inline]
+	pub fn set_max_guards(&mut self, max_guards: GuardCount) {
+		self.set_max_guards_inner(max_guards);
+	}
+	pub(crate) fn set_max_guards_inner(&mut self, max_guards: GuardCount) {
+		self.max_guards = max_guards.min(MAX_GUARD_COUNT);
+	}
+	
+	/// Returns the number of guards that [ScopedRef::acquire()] allows at once (which is [MAX_GUARD_COUNT] unless it was set)
+	#[inline]
+	pub fn max_guards(&self) -> GuardCount {
+		self.max_guards
+	}
+	
+	/// Blocks until there are fewer active guards than the limit set by [ScopedRef::with_max_guards()], then returns a new guard (is async on async runtimes)
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-none" ))]
+	pub fn acquire(self: &Pin<&mut Self>) -> ScopedRefGuard<ConnectorType> {
+		let this = self.as_ref().get_ref();
+		this.await_admission();
+		unsafe { this.make_guard() }
+	}
+	/// Blocks until there are fewer active guards than the limit set by [ScopedRef::with_max_guards()], then returns a new guard (is async on async runtimes)
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-none" ))]
+	pub fn acquire(&self) -> ScopedRefGuard<ConnectorType> {
+		self.await_admission();
+		self.make_guard()
+	}
+	/// Waits until there are fewer active guards than the limit set by [ScopedRef::with_max_guards()], then returns a new guard
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
+	pub async fn acquire(self: &Pin<&mut Self>) -> ScopedRefGuard<ConnectorType> {
+		let this = self.as_ref().get_ref();
+		this.await_admission().await;
+		unsafe { this.make_guard() }
+	}
+	/// Waits until there are fewer active guards than the limit set by [ScopedRef::with_max_guards()], then returns a new guard
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
+	pub async fn acquire(&self) -> ScopedRefGuard<ConnectorType> {
+		self.await_admission().await;
+		self.make_guard()
+	}
+	/// Same as [ScopedRef::acquire()], but blocks the current thread instead of being async (using `tokio::task::block_in_place()`)
+	#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
+	pub fn acquire_blocking(self: &Pin<&mut Self>) -> ScopedRefGuard<ConnectorType> {
+		let this = self.as_ref().get_ref();
+		tokio::task::block_in_place(|| Handle::current().block_on(this.await_admission()));
+		unsafe { this.make_guard() }
+	}
+	/// Same as [ScopedRef::acquire()], but blocks the current thread instead of being async (using `tokio::task::block_in_place()`)
+	#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
+	pub fn acquire_blocking(&self) -> ScopedRefGuard<ConnectorType> {
+		tokio::task::block_in_place(|| Handle::current().block_on(self.await_admission()));
+		self.make_guard()
+	}
+	
+	// increments the counter if there are fewer active guards than the limit
+	fn try_admit(&self) -> bool {
+		let Some(is_first) = self.header.counter.try_admit(self.max_guards) else { return false; };
+		if is_first { self.hooks().busy(); }
+		true
+	}
+	#[cfg(feature = "runtime-none")]
+	fn await_admission(&self) {
+		if self.try_admit() { return; }
+		self.header.waiting().wait_until(None, || self.try_admit());
+	}
+	#[cfg(feature = "runtime-tokio")]
+	async fn await_admission(&self) {
+		if self.try_admit() { return; }
+		self.header.waiting().wait_until(|| self.try_admit()).await;
+	}
+	
 	/// Blocks until all guards have been dropped (is async on async runtimes)
+	/// 
+	/// Returns information about any guards that were dropped during a panic (see [GuardPanics])
 	#[cfg(feature = "runtime-none")]
-	pub fn await_guards(&self, timeout: Option<Duration>) {
-		#[cfg(not(feature = "no-pin"))]
-		let (mutex, condvar) = (&self.counter_notify.1, &self.counter_notify.2);
-		#[cfg(feature = "no-pin")]
-		let (mutex, condvar) = (&self.counter_notify.0, &self.counter_notify.1);
-		if let Some(timeout) = timeout {
-			
-			let mut guard = mutex.lock().expect("failed to start waiting for data guards to drop");
-			if !self.has_active_guards() { return; } // doing this here ensures that a notification can't be sent after this check but before the `condvar.wait()`
-			let end = Instant::now() + timeout;
-			(guard, _) = condvar.wait_timeout(guard, timeout).expect("failed to wait for data guards to drop");
-			if !self.has_active_guards() { return; }
-			loop {
-				let now = Instant::now();
-				if now > end { return; }
-				(guard, _) = condvar.wait_timeout(guard, end - now).expect("failed to wait for data guards to drop");
-				if !self.has_active_guards() { return; }
-			}
-			
-		} else {
-			
-			let mut guard = mutex.lock().expect("failed to start waiting for data guards to drop");
-			loop {
-				if !self.has_active_guards() { return; }
-				guard = condvar.wait(guard).expect("failed to wait for data guards to drop");
-			}
-			
-		}
+	pub fn await_guards(&self, timeout: Option<Duration>) -> GuardPanics {
+		let deadline = timeout.map(|timeout| Instant::now() + timeout);
+		self.header.waiting().wait_until(deadline, || !self.has_active_guards());
+		self.guard_panics()
 	}
 	/// Blocks until all guards have been dropped (is async on async runtimes)
+	/// 
+	/// Returns information about any guards that were dropped during a panic (see [GuardPanics])
 	#[cfg(feature = "runtime-tokio")]
-	pub async fn await_guards(&self, timeout: Option<Duration>) {
-		#[cfg(not(feature = "no-pin"))]
-		let notify = &self.counter_notify.1;
-		#[cfg(feature = "no-pin")]
-		let notify = &*self.counter_notify;
-		let notify_future = notify.notified(); // creating the notify before checking the counter ensure no races
-		if !self.has_active_guards() { return; }
+	pub async fn await_guards(&self, timeout: Option<Duration>) -> GuardPanics {
+		let waiting = self.header.waiting();
+		let wait_future = waiting.wait_until(|| !self.has_active_guards());
 		if let Some(timeout) = timeout {
-			let _possible_notify_future = tokio::time::timeout(timeout, notify_future).await;
+			let _possible_wait_future = tokio::time::timeout(timeout, wait_future).await;
 		} else {
-			notify_future.await;
+			wait_future.await;
+		}
+		self.guard_panics()
+	}
+	
+	/// Returns information about the guards that were dropped during a panic so far (see [GuardPanics])
+	pub fn guard_panics(&self) -> GuardPanics {
+		self.header.panics.report()
+	}
+	
+	/// Sets a callback that is called whenever the last active guard is dropped (for example, to update a metrics gauge or release a pooled buffer). The callback runs on the thread that dropped the guard, right before the guard is released (if a guard is created while it runs, `on_busy` is called again afterwards)
+	/// 
+	/// This panics if the callback was already set, or if the "sharded-counter" feature is enabled (since guards can't know whether they're the last one)
+	#[track_caller]
+	pub fn on_idle(&self, f: impl Fn() + Send + Sync + 'static) {
+		ScopeHooks::check_supported();
+		if self.hooks().on_idle.set(Box::new(f)).is_err() { panic!("the `on_idle` callback of this `ScopedRef` was already set"); }
+	}
+	
+	/// Sets a callback that is called whenever a guard is created while there are no other active guards. The callback runs on the thread that created the guard
+	/// 
+	/// This panics if the callback was already set, or if the "sharded-counter" feature is enabled (since guards can't know whether they're the first one)
+	#[track_caller]
+	pub fn on_busy(&self, f: impl Fn() + Send + Sync + 'static) {
+		ScopeHooks::check_supported();
+		if self.hooks().on_busy.set(Box::new(f)).is_err() { panic!("the `on_busy` callback of this `ScopedRef` was already set"); }
+	}
+	
+	#[inline]
+	pub(crate) fn hooks(&self) -> &ScopeHooks {
+		&self.header.hooks
+	}
+	
+	/// Returns the name that was given with [ScopedRefBuilder::name()], if there is one
+	#[inline]
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+	
+	// used in diagnostics, so that named scopes can be identified
+	fn describe(&self) -> String {
+		match &self.name {
+			Some(name) => format!("`ScopedRef` \"{name}\""),
+			None => String::from("`ScopedRef`"),
 		}
 	}
 	
+	/// Returns the number of living `ScopedRefGuard`s (and `ScopedKeepAlive`s) created from this `ScopedRef`
+	#[inline]
+	#[allow(clippy::useless_conversion)]
+	pub fn active_guard_count(&self) -> u64 {
+		u64::from(self.header.counter.count())
+	}
+	
 	/// Returns whether there are still living `ScopedRefGuard`s that would cause dropping this `ScopedRef` to block
 	#[inline]
 	pub fn has_active_guards(&self) -> bool {
-		#[cfg(all(not(feature = "no-pin"), feature = "runtime-none" ))]
-		{ self.counter_notify.0.load(Ordering::Acquire) > 0}
-		#[cfg(all(    feature = "no-pin" , feature = "runtime-none" ))]
-		{ Arc::strong_count(&self.counter_notify) > 1 }
-		#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
-		{ self.counter_notify.0.load(Ordering::Acquire) > 0}
-		#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
-		{ Arc::strong_count(&self.counter_notify) > 1 }
+		self.header.counter.count() > 0
 	}
 	
 }
 
 // When `ScopedRef` is dropped, it must wait until all `ScopedRefGuards` have been dropped before continuing execution (unless a different feature is enabled)
-impl<'a, ConnectorType: TypeConnector> Drop for ScopedRef<'a, ConnectorType> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+impl<'a, ConnectorType: TypeConnector> Drop for ScopedRef<'a, ConnectorType> {
 	fn drop(&mut self) {
+		self.apply_drop_policy();
+	}
+}
+
+impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> {
+	// waits for the guards (or aborts / panics / does nothing) depending on the enabled features, which is also used when a `ScopedRefBinding` ends
+	pub(crate) fn apply_drop_policy(&self) {
 		#[cfg(feature = "unwind-does-abort")]
 		if std::thread::panicking() {
-			eprintln!("Program must be aborted due to a `ScopedRef` being dropped on unwind.");
+			eprintln!("Program must be aborted due to a {} being dropped on unwind.", self.describe());
 			std::process::abort();
 		}
 		#[cfg(feature = "unsafe-ignore-unwind")]
@@ -191,27 +424,38 @@ impl<'a, ConnectorType: TypeConnector> Drop for ScopedRef<'a, ConnectorType> whe
 		{
 			#[cfg(feature = "runtime-none")]
 			{
-				self.await_guards(None);
+				self.await_guards(self.drop_timeout);
 			}
 			#[cfg(feature = "runtime-tokio")]
 			{
-				tokio::task::block_in_place(move || {
-					Handle::current().block_on(async {
-						self.await_guards(None).await;
-					})
-				});
+				let wait = async { self.await_guards(self.drop_timeout).await; };
+				match Handle::try_current() {
+					Ok(handle) => tokio::task::block_in_place(move || handle.block_on(wait)),
+					// this can be dropped outside of any runtime (for example, a `SubScope` that was sent to a plain thread), and unwinding here would free the data while guards still use it, so this waits on a temporary runtime instead
+					Err(_) => match tokio::runtime::Builder::new_current_thread().enable_time().build() {
+						Ok(runtime) => runtime.block_on(wait),
+						Err(err) => {
+							eprintln!("Program must be aborted due to a {} being dropped outside of a tokio runtime (failed to create a runtime to wait on: {err})", self.describe());
+							std::process::abort();
+						}
+					}
+				}
+			}
+			if self.drop_timeout.is_some() && self.has_active_guards() {
+				eprintln!("Program must be aborted due to a {} still having active guards after its drop timeout", self.describe());
+				std::process::abort();
 			}
 		}
 		#[cfg(feature = "drop-does-abort")]
 		{
 			if self.has_active_guards() {
-				eprintln!("Attempting to drop a `ScopedRef` while it still has active guards");
+				eprintln!("Attempting to drop a {} while it still has active guards", self.describe());
 				std::process::abort()
 			}
 		}
 		#[cfg(feature = "unsafe-drop-does-panic")]
 		{
-			if self.has_active_guards() { panic!("Attempting to drop a `ScopedRef` while it still has active guards"); }
+			if self.has_active_guards() { panic!("Attempting to drop a {} while it still has active guards", self.describe()); }
 		}
 		#[cfg(feature = "unsafe-drop-does-nothing")]
 		{}
@@ -0,0 +1,135 @@
+use crate::*;
+use std::{borrow::Cow, time::Duration, marker::PhantomData, pin::Pin};
+
+
+
+/// Configures a [ScopedRef] at runtime, instead of only through crate features. To create this type, use [ScopedRef::builder()]
+/// 
+/// The built `ScopedRef` can be pinned on the stack by giving the builder to the [make_scoped_ref] macro, as in `make_scoped_ref!(scope_var_name = (reference_to_scope) as ConnectorType, builder);`
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// make_scoped_ref!(scoped_frame = (&*frame) as Slice<u8>, ScopedRef::builder()
+///     .name("frame-buffer")
+///     .max_guards(8)
+///     .drop_timeout(Duration::from_secs(5))
+///     .on_idle(|| metrics::gauge!("frame_buffer_busy").set(0.0))
+/// );
+/// ```
+pub struct ScopedRefBuilder<ConnectorType: TypeConnector> {
+	pub(crate) name: Option<Cow<'static, str>>,
+	pub(crate) max_guards: Option<GuardCount>,
+	pub(crate) drop_timeout: Option<Duration>,
+	pub(crate) on_idle: Option<Box<dyn Fn() + Send + Sync>>,
+	pub(crate) on_busy: Option<Box<dyn Fn() + Send + Sync>>,
+	pub(crate) phantom: PhantomData<fn() -> ConnectorType>,
+}
+
+impl<'a, ConnectorType: TypeConnector> ScopedRef<'a, ConnectorType> {
+	/// Returns a [ScopedRefBuilder], which allows the `ScopedRef` to be configured at runtime
+	#[inline]
+	pub fn builder() -> ScopedRefBuilder<ConnectorType> {
+		ScopedRefBuilder::new()
+	}
+}
+
+impl<ConnectorType: TypeConnector> ScopedRefBuilder<ConnectorType> {
+	
+	/// Creates a new builder with the default configuration (which is the same as `ScopedRef::new()`)
+	#[inline]
+	pub fn new() -> Self {
+		Self {
+			name: None,
+			max_guards: None,
+			drop_timeout: None,
+			on_idle: None,
+			on_busy: None,
+			phantom: PhantomData,
+		}
+	}
+	
+	/// Sets the name of the `ScopedRef`, which is included in its diagnostics (like the message when it aborts the program)
+	#[inline]
+	pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+		self.name = Some(name.into());
+		self
+	}
+	
+	/// Sets the number of guards that [ScopedRef::acquire()] allows at once (see [ScopedRef::with_max_guards()])
+	#[inline]
+	pub fn max_guards(mut self, max_guards: GuardCount) -> Self {
+		self.max_guards = Some(max_guards);
+		self
+	}
+	
+	/// Sets how long dropping the `ScopedRef` can block (with the "drop-does-block" feature). If there are still active guards after this time, the program is aborted, since continuing would create dangling pointers
+	#[inline]
+	pub fn drop_timeout(mut self, drop_timeout: Duration) -> Self {
+		self.drop_timeout = Some(drop_timeout);
+		self
+	}
+	
+	/// Sets a callback for when the last active guard is dropped (see [ScopedRef::on_idle()], this also panics with the "sharded-counter" feature)
+	#[track_caller]
+	#[inline]
+	pub fn on_idle(mut self, f: impl Fn() + Send + Sync + 'static) -> Self {
+		ScopeHooks::check_supported();
+		self.on_idle = Some(Box::new(f));
+		self
+	}
+	
+	/// Sets a callback for when a guard is created while there are no other active guards (see [ScopedRef::on_busy()], this also panics with the "sharded-counter" feature)
+	#[track_caller]
+	#[inline]
+	pub fn on_busy(mut self, f: impl Fn() + Send + Sync + 'static) -> Self {
+		ScopeHooks::check_supported();
+		self.on_busy = Some(Box::new(f));
+		self
+	}
+	
+	/// NOTE: the built `ScopedRef` is meant to be created using the [make_scoped_ref] macro.
+	/// 
+	/// Creates a new `ScopedRef` with a given reference and this configuration
+	/// 
+	/// # Safety
+	/// 
+	/// See [ScopedRef::new()]
+	pub unsafe fn build<'a>(self, data: impl Into<&'a ConnectorType::Super<'a>>) -> ScopedRef<'a, ConnectorType> where &'a ConnectorType::Super<'a>: Copy {
+		let mut output = unsafe { ScopedRef::new(data) };
+		if let Some(max_guards) = self.max_guards { output.set_max_guards_inner(max_guards); }
+		if let Some(on_idle) = self.on_idle { let _ = output.hooks().on_idle.set(on_idle); }
+		if let Some(on_busy) = self.on_busy { let _ = output.hooks().on_busy.set(on_busy); }
+		output.name = self.name;
+		output.drop_timeout = self.drop_timeout;
+		output
+	}
+	
+	/// Same as [ScopedRefBuilder::build()], but pins the `ScopedRef` on the heap so that it can be stored anywhere
+	/// 
+	/// # Safety
+	/// 
+	/// See [ScopedRef::new()] (in this case, the `Box` must not be forgotten)
+	#[inline]
+	pub unsafe fn build_pinned<'a>(self, data: impl Into<&'a ConnectorType::Super<'a>>) -> Pin<Box<ScopedRef<'a, ConnectorType>>> where &'a ConnectorType::Super<'a>: Copy {
+		Box::pin(unsafe { self.build(data) })
+	}
+	
+}
+
+impl<ConnectorType: TypeConnector> Default for ScopedRefBuilder<ConnectorType> {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<ConnectorType: TypeConnector> std::fmt::Debug for ScopedRefBuilder<ConnectorType> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ScopedRefBuilder")
+			.field("name", &self.name)
+			.field("max_guards", &self.max_guards)
+			.field("drop_timeout", &self.drop_timeout)
+			.finish_non_exhaustive()
+	}
+}
@@ -1,15 +1,8 @@
 use crate::*;
-use std::marker::PhantomData;
+use std::{borrow::Borrow, ffi::{CStr, OsStr}, marker::PhantomData, ops::Deref, path::Path};
 
-#[cfg(feature = "runtime-none" )]
-use std::sync::{Mutex, Condvar};
-#[cfg(feature = "runtime-tokio")]
-use tokio::sync::Notify;
-
-#[cfg(not(feature = "no-pin"))]
-use std::sync::atomic::AtomicU32;
 #[cfg(not(feature = "no-pin"))]
-use std::sync::atomic::Ordering;
+use std::ptr::NonNull;
 #[cfg(feature = "no-pin")]
 use std::sync::Arc;
 
@@ -17,29 +10,22 @@ use std::sync::Arc;
 
 /// Similar to something like `MutexGuard`, but for keeping track of the number of references.
 /// 
+/// A guard is only a single pointer to its `ScopedRef`'s header (which holds the data pointer), so `Option<ScopedRefGuard<_>>` is also pointer-sized
+/// 
 /// Note: this type only implements `Send` and/or `Sync` when the underlying reference implements `Send` and/or `Sync`
-pub struct ScopedRefGuard<ConnectorType: TypeConnector> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+pub struct ScopedRefGuard<ConnectorType: TypeConnector> {
 	
-	pub(crate) data_ptr: [u8; std::mem::size_of::<&ConnectorType::Super<'static>>()],
-	
-	// stores the counter and the notify together, which allows the `Arc<Notify>` when "no-pin" and "runtime-tokio" are used together
-	#[cfg(all(not(feature = "no-pin"), feature = "runtime-none" ))]
-	pub(crate) counter_notify: (&'static AtomicU32, &'static Mutex<()>, &'static Condvar),
-	#[cfg(all(    feature = "no-pin" , feature = "runtime-none" ))]
-	pub(crate) counter_notify: Arc<(Mutex<()>, Condvar)>,
-	#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
-	pub(crate) counter_notify: (&'static AtomicU32, &'static Notify),
-	#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
-	pub(crate) counter_notify: Arc<Notify>,
+	// this is what actually keeps the `ScopedRef` alive (and it is shared with the type-erased guards, so they all use the same drop logic)
+	pub(crate) keep_alive: ScopedKeepAlive,
 	
 	pub(crate) phantom: PhantomData<*mut ConnectorType>, // NOTE: the `*mut` is used to intentionally make `ScopedRefGuard` not Send/Sync
 	
 }
 
-unsafe impl<ConnectorType: TypeConnector> Send for ScopedRefGuard<ConnectorType> where for<'a> <ConnectorType as TypeConnector>::Super<'a>: Send, [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {}
-unsafe impl<ConnectorType: TypeConnector> Sync for ScopedRefGuard<ConnectorType> where for<'a> <ConnectorType as TypeConnector>::Super<'a>: Sync, [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {}
+unsafe impl<ConnectorType: TypeConnector> Send for ScopedRefGuard<ConnectorType> where for<'a> <ConnectorType as TypeConnector>::Super<'a>: Send {}
+unsafe impl<ConnectorType: TypeConnector> Sync for ScopedRefGuard<ConnectorType> where for<'a> <ConnectorType as TypeConnector>::Super<'a>: Sync {}
 
-impl<ConnectorType: TypeConnector> ScopedRefGuard<ConnectorType> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+impl<ConnectorType: TypeConnector> ScopedRefGuard<ConnectorType> {
 	/// Returns the inner data. This is similar to `deref()` from the `Deref` trait, but is separate because it requires special lifetimes
 	#[inline]
 	pub fn inner<'a>(&'a self) -> &'a ConnectorType::Super<'a> {
@@ -51,76 +37,145 @@ impl<ConnectorType: TypeConnector> ScopedRefGuard<ConnectorType> where [(); std:
 		4: `T` can only be dropped after all references to `T` given by this function are dropped
 		*/
 		unsafe {
-			// SAFETY (size): the type for `data_ptr` ensures that it is the same size as `&ConnectorType::Super`
-			&*(&self.data_ptr as *const _ as *const &'a ConnectorType::Super<'a>)
+			// SAFETY (layout): `Super<'static>` and `Super<'a>` only differ in lifetimes, so they always have the same layout
+			// SAFETY (type): a `ScopedRefGuard<ConnectorType>` is only created by a `ScopedRef<ConnectorType>`
+			std::mem::transmute::<&'a ConnectorType::Super<'static>, &'a ConnectorType::Super<'a>>(self.keep_alive.header().data_ptr::<ConnectorType>().as_ref())
 		}
 	}
-}
-
-impl<ConnectorType: TypeConnector> Drop for ScopedRefGuard<ConnectorType> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
-	fn drop(&mut self) {
-		#[cfg(not(feature = "no-pin"))]
-		{
-			let prev_count = self.counter_notify.0.fetch_sub(1, Ordering::AcqRel);
-			if prev_count == 1 {
-				#[cfg(feature = "runtime-none")]
-				{
-					// locking the mutex is necessary to prevent sending a notification after the main ScopedRef checks the active count but before it waits on the condvar
-					let lock = self.counter_notify.1.lock().expect("failed to lock mutex while dropping data guard");
-					self.counter_notify.2.notify_all();
-					drop(lock);
-				}
-				#[cfg(feature = "runtime-tokio")]
-				self.counter_notify.1.notify_waiters();
-			}
-		}
-		#[cfg(feature = "no-pin")]
-		{
-			#[cfg(feature = "runtime-none")]
-			if Arc::strong_count(&self.counter_notify) == 2 {
-				// locking the mutex is necessary to prevent sending a notification after the main ScopedRef checks the active count but before it waits on the condvar
-				let lock = self.counter_notify.0.lock().expect("failed to lock mutex while dropping data guard");
-				self.counter_notify.1.notify_all();
-				drop(lock);
-			}
-			#[cfg(feature = "runtime-tokio")]
-			if Arc::strong_count(&self.counter_notify) == 2 {
-				self.counter_notify.notify_waiters();
-			}
-		}
+	
+	/// Returns a new [ScopedKeepAlive], which keeps the `ScopedRef` alive without giving access to its data
+	#[inline]
+	pub fn keep_alive(&self) -> ScopedKeepAlive {
+		self.keep_alive.clone()
 	}
 }
 
-impl<ConnectorType: TypeConnector> std::fmt::Debug for ScopedRefGuard<ConnectorType> where for<'a> ConnectorType::Super<'a>: std::fmt::Debug, [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+impl<ConnectorType: TypeConnector> std::fmt::Debug for ScopedRefGuard<ConnectorType> where for<'a> ConnectorType::Super<'a>: std::fmt::Debug {
 	#[inline]
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		self.inner().fmt(f)
 	}
 }
 
-impl<ConnectorType: TypeConnector> std::fmt::Display for ScopedRefGuard<ConnectorType> where for<'a> ConnectorType::Super<'a>: std::fmt::Display, [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+impl<ConnectorType: TypeConnector> std::fmt::Display for ScopedRefGuard<ConnectorType> where for<'a> ConnectorType::Super<'a>: std::fmt::Display {
 	#[inline]
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		self.inner().fmt(f)
 	}
 }
 
-impl<ConnectorType: TypeConnector> Clone for ScopedRefGuard<ConnectorType> where [(); std::mem::size_of::<&ConnectorType::Super<'static>>()]: Sized {
+impl<ConnectorType: StaticConnector> Deref for ScopedRefGuard<ConnectorType> {
+	type Target = ConnectorType::Target;
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		ConnectorType::as_target(self.inner())
+	}
+}
+
+impl<ConnectorType: StaticConnector> AsRef<ConnectorType::Target> for ScopedRefGuard<ConnectorType> {
+	#[inline]
+	fn as_ref(&self) -> &ConnectorType::Target {
+		ConnectorType::as_target(self.inner())
+	}
+}
+
+// `Borrow` can't be implemented for every `StaticConnector` (since it would overlap with `impl<T> Borrow<T> for T`), so it is only implemented for the built-in connectors
+macro_rules! impl_borrow {
+	($([$($generics:tt)*] $connector:ty => $target:ty;)+) => {$(
+		impl<$($generics)*> Borrow<$target> for ScopedRefGuard<$connector> {
+			#[inline]
+			fn borrow(&self) -> &$target {
+				self.inner()
+			}
+		}
+	)+};
+}
+impl_borrow! {
+	[T: ?Sized + 'static] Ref<T> => T;
+	[T: 'static] Slice<T> => [T];
+	[] Str => str;
+	[] PathRef => Path;
+	[] OsStrRef => OsStr;
+	[] CStrRef => CStr;
+}
+
+impl<ConnectorType: TypeConnector> Clone for ScopedRefGuard<ConnectorType> {
 	#[inline]
 	fn clone(&self) -> Self {
-		#[cfg(not(feature = "no-pin"))]
-		self.counter_notify.0.fetch_add(1, Ordering::AcqRel);
 		Self {
-			data_ptr: self.data_ptr,
-			#[cfg(all(not(feature = "no-pin"), feature = "runtime-none" ))]
-			counter_notify: self.counter_notify,
-			#[cfg(all(    feature = "no-pin" , feature = "runtime-none" ))]
-			counter_notify: self.counter_notify.clone(),
-			#[cfg(all(not(feature = "no-pin"), feature = "runtime-tokio"))]
-			counter_notify: self.counter_notify,
-			#[cfg(all(    feature = "no-pin" , feature = "runtime-tokio"))]
-			counter_notify: self.counter_notify.clone(),
+			keep_alive: self.keep_alive.clone(),
 			phantom: PhantomData,
 		}
 	}
 }
+
+
+
+/// Keeps a [ScopedRef] alive without giving access to its data. This is the simplest type-erased form of a [ScopedRefGuard], and can be created with [ScopedRefGuard::keep_alive()] or `ScopedKeepAlive::from(guard)`
+/// 
+/// Like a guard, the `ScopedRef` that this came from cannot be dropped until this is dropped
+pub struct ScopedKeepAlive {
+	
+	// points to the header of the `ScopedRef`, which stays valid (and doesn't move) until the counter reaches zero
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) header: NonNull<ScopeHeader>,
+	#[cfg(feature = "no-pin")]
+	pub(crate) header: Arc<ScopeHeader>,
+	
+}
+
+// SAFETY: this only gives access to the header, which is `Send + Sync`
+unsafe impl Send for ScopedKeepAlive {}
+unsafe impl Sync for ScopedKeepAlive {}
+
+impl ScopedKeepAlive {
+	#[inline]
+	pub(crate) fn header(&self) -> &ScopeHeader {
+		#[cfg(not(feature = "no-pin"))]
+		{
+			// SAFETY: the `ScopedRef` can't be dropped or moved while this is alive
+			unsafe { self.header.as_ref() }
+		}
+		#[cfg(feature = "no-pin")]
+		{ &self.header }
+	}
+}
+
+impl Drop for ScopedKeepAlive {
+	fn drop(&mut self) {
+		let header = self.header();
+		#[cfg(debug_assertions)]
+		if !std::thread::panicking() { header.check_generation(); }
+		if std::thread::panicking() {
+			header.panics.record();
+		}
+		header.release_guard(); // the `ScopedRef` might not exist after this
+		
+	}
+}
+
+impl Clone for ScopedKeepAlive {
+	#[inline]
+	fn clone(&self) -> Self {
+		self.header().counter.increment(); // this can't be the first active guard, so `on_busy` never needs to be called here
+		Self {
+			#[cfg(not(feature = "no-pin"))]
+			header: self.header,
+			#[cfg(feature = "no-pin")]
+			header: self.header.clone(),
+		}
+	}
+}
+
+impl std::fmt::Debug for ScopedKeepAlive {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ScopedKeepAlive").finish_non_exhaustive()
+	}
+}
+
+impl<ConnectorType: TypeConnector> From<ScopedRefGuard<ConnectorType>> for ScopedKeepAlive {
+	#[inline]
+	fn from(data_ref: ScopedRefGuard<ConnectorType>) -> Self {
+		data_ref.keep_alive
+	}
+}
@@ -0,0 +1,170 @@
+use crate::*;
+use std::{time::Duration, marker::PhantomData, sync::{Arc, PoisonError, RwLock}};
+
+
+
+/// Creates a new [ScopedSwap] and assigns it to a variable. This uses the format `make_scoped_swap!(swap_var_name = (reference_to_first_version) as ConnectorType);`
+/// 
+/// Like [make_scoped_ref], this makes sure that the `ScopedSwap` can't be forgotten (which would allow guards to outlive the data). Every version given to [ScopedSwap::publish()] also has to outlive the `ScopedSwap`, so this doesn't compile:
+/// 
+/// ```compile_fail,E0597
+/// use ::scoped_ref::*;
+/// let first = String::from("first");
+/// make_scoped_swap!(swap = (&*first) as Str);
+/// let guard;
+/// {
+///     let second = String::from("second");
+///     swap.publish(&*second);
+///     guard = swap.load();
+/// }
+/// println!("{}", guard.inner());
+/// ```
+#[macro_export]
+macro_rules! make_scoped_swap {
+	($scope:ident = ($input:expr) as $connector:ty) => {
+		let $scope: &<$connector as $crate::TypeConnector>::Super<'_> = $input;
+		let $scope = &unsafe {
+			$crate::ScopedSwap::<$connector>::new($scope)
+		};
+	};
+}
+
+
+
+/// Holds several versions of scoped data, where readers always get guards to the latest version (similar to RCU). To create this type, the [make_scoped_swap] macro is recommended
+/// 
+/// Every version has its own guard counter, so publishing a new version never waits for the readers of older versions. Old versions are released once all of their guards are dropped, and when this is dropped, it follows the same drop policy as [ScopedRef] for every version that is still in use
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// let arena = ConfigArena::new();
+/// make_scoped_swap!(config = (arena.load_config()) as ConfigConnector);
+/// spawn_readers(config.load());
+/// loop {
+///     wait_for_change();
+///     config.publish(arena.load_config()); // new calls to `load()` get this version
+/// }
+/// ```
+pub struct ScopedSwap<'a, ConnectorType: TypeConnector> {
+	pub(crate) versions: RwLock<SwapVersions<ConnectorType>>,
+	// `'a` is invariant, so `publish()` can't shrink it to fit a reference that doesn't live as long as the `ScopedSwap`
+	pub(crate) phantom: PhantomData<&'a mut &'a ConnectorType>,
+}
+
+pub(crate) struct SwapVersions<ConnectorType: TypeConnector> {
+	// the versions are stored in `Arc`s so that they never move (even without "no-pin"), and so they can be waited on without holding the lock
+	pub(crate) current: Arc<ScopedRef<'static, ConnectorType>>,
+	pub(crate) retired: Vec<Arc<ScopedRef<'static, ConnectorType>>>,
+	pub(crate) version: u64,
+}
+
+impl<'a, ConnectorType: TypeConnector> ScopedSwap<'a, ConnectorType> {
+	
+	/// NOTE: `ScopedSwap` is meant to be created using the [make_scoped_swap] macro.
+	/// 
+	/// Creates a new `ScopedSwap` with the given reference as its first version
+	/// 
+	/// # Safety
+	/// 
+	/// This function is considered unsafe because it is possible to create dangling pointers with this if you 1: create a `ScopedSwap` with this, 2: create a `ScopedRefGuard` with the swap, 3: use `std::mem::forget()` to drop the `ScopedSwap`, and 4: drop the data that any version referenced
+	pub unsafe fn new(data: impl Into<&'a ConnectorType::Super<'a>>) -> Self where &'a ConnectorType::Super<'a>: Copy {
+		Self {
+			versions: RwLock::new(SwapVersions {
+				current: Arc::new(unsafe { Self::new_version(data.into()) }),
+				retired: Vec::new(),
+				version: 1,
+			}),
+			phantom: PhantomData,
+		}
+	}
+	
+	// SAFETY: the version must be dropped before the end of `'a`
+	unsafe fn new_version(data: &'a ConnectorType::Super<'a>) -> ScopedRef<'static, ConnectorType> {
+		// SAFETY: the versions are owned by the `ScopedSwap`, which can't outlive `'a`
+		let data = unsafe { std::mem::transmute::<&'a ConnectorType::Super<'a>, &'static ConnectorType::Super<'static>>(data) };
+		unsafe { ScopedRef::new(data) }
+	}
+	
+	/// Returns a new guard to the latest version of the data
+	pub fn load(&self) -> ScopedRefGuard<ConnectorType> {
+		let versions = self.versions.read().unwrap_or_else(PoisonError::into_inner);
+		#[cfg(not(feature = "no-pin"))]
+		{
+			// SAFETY: the version is inside an `Arc`, so it can never move
+			unsafe { versions.current.new_ref_unchecked() }
+		}
+		#[cfg(feature = "no-pin")]
+		{ versions.current.new_ref() }
+	}
+	
+	/// Makes the given reference the latest version, and returns its version number (the first version is 1). Guards to older versions stay valid, and old versions are released once their guards are dropped
+	pub fn publish(&self, data: &'a ConnectorType::Super<'a>) -> u64 {
+		let new_version = Arc::new(unsafe { Self::new_version(data) });
+		let mut versions = self.versions.write().unwrap_or_else(PoisonError::into_inner);
+		let old_version = std::mem::replace(&mut versions.current, new_version);
+		versions.retired.push(old_version);
+		versions.retired.retain(|version| version.has_active_guards());
+		versions.version += 1;
+		versions.version
+	}
+	
+	/// Returns the number of the latest version (the first version is 1)
+	#[inline]
+	pub fn version(&self) -> u64 {
+		self.versions.read().unwrap_or_else(PoisonError::into_inner).version
+	}
+	
+	/// Returns the number of old versions that still have active guards
+	pub fn retired_versions(&self) -> usize {
+		let mut versions = self.versions.write().unwrap_or_else(PoisonError::into_inner);
+		versions.retired.retain(|version| version.has_active_guards());
+		versions.retired.len()
+	}
+	
+	/// Blocks until every guard to an old version has been dropped (is async on async runtimes). See [ScopedRef::await_guards()]
+	/// 
+	/// The timeout is applied to each old version separately
+	#[cfg(feature = "runtime-none")]
+	pub fn await_retired(&self, timeout: Option<Duration>) -> GuardPanics {
+		let mut output = GuardPanics::default();
+		for version in self.retired() {
+			output.merge(version.await_guards(timeout));
+		}
+		output
+	}
+	/// Waits until every guard to an old version has been dropped. See [ScopedRef::await_guards()]
+	/// 
+	/// The timeout is applied to each old version separately
+	#[cfg(feature = "runtime-tokio")]
+	pub async fn await_retired(&self, timeout: Option<Duration>) -> GuardPanics {
+		let mut output = GuardPanics::default();
+		for version in self.retired() {
+			output.merge(version.await_guards(timeout).await);
+		}
+		output
+	}
+	
+	// clones the old versions, so that they can be waited on without holding the lock
+	fn retired(&self) -> Vec<Arc<ScopedRef<'static, ConnectorType>>> {
+		self.versions.read().unwrap_or_else(PoisonError::into_inner).retired.clone()
+	}
+	
+	/// Returns whether any version (including the latest one) still has living `ScopedRefGuard`s that would cause dropping this `ScopedSwap` to block
+	pub fn has_active_guards(&self) -> bool {
+		let versions = self.versions.read().unwrap_or_else(PoisonError::into_inner);
+		versions.current.has_active_guards() || versions.retired.iter().any(|version| version.has_active_guards())
+	}
+	
+}
+
+// When `ScopedSwap` is dropped, it applies the drop policy of `ScopedRef` to every version. Having a `Drop` impl also makes the borrow checker require every published reference to outlive the `ScopedSwap`, since the versions themselves are `'static`
+impl<'a, ConnectorType: TypeConnector> Drop for ScopedSwap<'a, ConnectorType> {
+	fn drop(&mut self) {
+		let versions = self.versions.get_mut().unwrap_or_else(PoisonError::into_inner);
+		versions.current.apply_drop_policy();
+		for version in &versions.retired {
+			version.apply_drop_policy();
+		}
+	}
+}
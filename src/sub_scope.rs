@@ -0,0 +1,102 @@
+use crate::*;
+use std::time::Duration;
+
+#[cfg(not(feature = "no-pin"))]
+use std::pin::Pin;
+
+
+
+/// A child scope which lends out part of the data from a [ScopedRefGuard] (for example, one field of a struct). To create this type, use [ScopedRefGuard::sub_scope()]
+/// 
+/// The guards given out by this are `'static` just like normal guards, and this holds the parent guard until every child guard is dropped. When this is dropped, it follows the same drop policy as [ScopedRef] (for example, blocking until every child guard is dropped when the "drop-does-block" feature is enabled), and then it drops the parent guard
+pub struct SubScope<ParentConnector: TypeConnector, ConnectorType: TypeConnector> {
+	
+	// NOTE: the order of these fields matters, since the child scope must be dropped (and therefore wait for its guards) before the parent guard is dropped
+	#[cfg(not(feature = "no-pin"))]
+	pub(crate) scope: Pin<Box<ScopedRef<'static, ConnectorType>>>,
+	#[cfg(feature = "no-pin")]
+	pub(crate) scope: ScopedRef<'static, ConnectorType>,
+	
+	pub(crate) parent: ScopedRefGuard<ParentConnector>,
+	
+}
+
+impl<ParentConnector: TypeConnector> ScopedRefGuard<ParentConnector> {
+	/// Creates a child scope which lends out the data returned by `f` (like a field of the data that this guard references). The child scope holds this guard until every guard created from the child scope is dropped
+	/// 
+	/// ### Example:
+	/// 
+	/// ```ignore
+	/// make_type_connector!(RefField = <'a> Field);
+	/// let sub_scope = data_ref.sub_scope::<RefField>(|data| &data.field);
+	/// let field_ref: ScopedRefGuard<RefField> = sub_scope.new_ref();
+	/// ```
+	pub fn sub_scope<ConnectorType: TypeConnector>(self, f: impl for<'b> FnOnce(&'b ParentConnector::Super<'b>) -> &'b ConnectorType::Super<'b>) -> SubScope<ParentConnector, ConnectorType> {
+		let data = f(self.inner());
+		// SAFETY (lifetime): the data is owned by the parent scope, which can't be dropped until the parent guard is dropped, which is only dropped after the child scope is dropped
+		let data = unsafe { std::mem::transmute::<&ConnectorType::Super<'_>, &'static ConnectorType::Super<'static>>(data) };
+		// SAFETY (forgetting): if the child scope is leaked, the parent guard is also leaked, so the data will never be dropped
+		let scope = unsafe { ScopedRef::new(data) };
+		SubScope {
+			#[cfg(not(feature = "no-pin"))]
+			scope: Box::pin(scope),
+			#[cfg(feature = "no-pin")]
+			scope,
+			parent: self,
+		}
+	}
+}
+
+impl<ParentConnector: TypeConnector, ConnectorType: TypeConnector> SubScope<ParentConnector, ConnectorType> {
+	
+	/// Returns a new guard that can be used to access the child data as if it is `'static`
+	#[inline]
+	pub fn new_ref(&self) -> ScopedRefGuard<ConnectorType> {
+		#[cfg(not(feature = "no-pin"))]
+		{
+			// SAFETY: the child scope is boxed and pinned, so it can never move
+			unsafe { self.scope.new_ref_unchecked() }
+		}
+		#[cfg(feature = "no-pin")]
+		{ self.scope.new_ref() }
+	}
+	
+	/// Blocks until all child guards have been dropped (is async on async runtimes). See [ScopedRef::await_guards()]
+	#[cfg(feature = "runtime-none")]
+	#[inline]
+	pub fn await_guards(&self, timeout: Option<Duration>) -> GuardPanics {
+		self.scope.await_guards(timeout)
+	}
+	/// Waits until all child guards have been dropped. See [ScopedRef::await_guards()]
+	#[cfg(feature = "runtime-tokio")]
+	#[inline]
+	pub async fn await_guards(&self, timeout: Option<Duration>) -> GuardPanics {
+		self.scope.await_guards(timeout).await
+	}
+	
+	/// Returns information about the child guards that were dropped during a panic so far (see [GuardPanics])
+	#[inline]
+	pub fn guard_panics(&self) -> GuardPanics {
+		self.scope.guard_panics()
+	}
+	
+	/// Returns whether there are still living child guards that would cause dropping this `SubScope` to block
+	#[inline]
+	pub fn has_active_guards(&self) -> bool {
+		self.scope.has_active_guards()
+	}
+	
+	/// Returns the parent guard
+	#[inline]
+	pub fn parent(&self) -> &ScopedRefGuard<ParentConnector> {
+		&self.parent
+	}
+	
+	/// Drops the child scope (following the same drop policy as [ScopedRef]) and returns the parent guard
+	pub fn into_parent(self) -> ScopedRefGuard<ParentConnector> {
+		let Self { scope, parent } = self;
+		drop(scope);
+		parent
+	}
+	
+}
@@ -13,6 +13,14 @@ cargo test --release --no-default-features --features drop-does-block,unwind-doe
 cargo test --release --no-default-features --features drop-does-block,unwind-does-abort,runtime-tokio
 cargo test --release --no-default-features --features drop-does-block,unwind-does-abort,runtime-none,no-pin
 cargo test --release --no-default-features --features drop-does-block,unwind-does-abort,runtime-tokio,no-pin
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-none,rayon
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-tokio,rayon
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-none,derive
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-tokio,derive
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-none,guard-count-u64
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-tokio,guard-count-u64
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-none,sharded-counter
+cargo test --no-default-features --features drop-does-block,unwind-does-abort,runtime-tokio,sharded-counter
 */
 
 
@@ -109,11 +117,23 @@ async fn advanced_type_test() {
 #[test]
 fn test_macro() {
 	
-	make_type_connector!(MyType *5 = <'a> Vec<&'a u8>);
+	make_type_connector!(MyType = <'a> Vec<&'a u8>);
 	
 	let inner_data = 0u8;
 	let _: <MyType as TypeConnector>::Super<'_> = vec!(&inner_data);
 	
+	mod connectors {
+		use crate::*;
+		make_type_connector!(
+			/// A public connector with generics and attributes
+			#[derive(Debug)]
+			pub VecOf<T: Clone + std::fmt::Debug, U,> = <'a> Vec<(T, U)> where U: Into<u8>
+		);
+		make_type_connector!(pub(crate) TwoRefs = <'a, 'b> (&'a u8, &'b u8));
+	}
+	let _: <connectors::VecOf<u8, u8> as TypeConnector>::Super<'_> = vec!((0, 1));
+	let _: <connectors::TwoRefs as TypeConnector>::Super<'_> = (&inner_data, &inner_data);
+	
 }
 #[cfg(feature = "runtime-tokio")]
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
@@ -124,6 +144,18 @@ async fn test_macro() {
 	let inner_data = 0u8;
 	let _: <MyType as TypeConnector>::Super<'_> = vec!(&inner_data);
 	
+	mod connectors {
+		use crate::*;
+		make_type_connector!(
+			/// A public connector with generics and attributes
+			#[derive(Debug)]
+			pub VecOf<T: Clone + std::fmt::Debug, U,> = <'a> Vec<(T, U)> where U: Into<u8>
+		);
+		make_type_connector!(pub(crate) TwoRefs = <'a, 'b> (&'a u8, &'b u8));
+	}
+	let _: <connectors::VecOf<u8, u8> as TypeConnector>::Super<'_> = vec!((0, 1));
+	let _: <connectors::TwoRefs as TypeConnector>::Super<'_> = (&inner_data, &inner_data);
+	
 }
 
 
@@ -131,8 +163,6 @@ async fn test_macro() {
 #[cfg(feature = "runtime-none")]
 #[test]
 fn test_std_traits() {
-	#[cfg(not(feature = "no-pin"))]
-	use std::sync::atomic::Ordering;
 	#[cfg(feature = "no-pin")]
 	use std::sync::Arc;
 	
@@ -150,9 +180,9 @@ fn test_std_traits() {
 	
 	let data_ref_2 = data_ref.clone();
 	#[cfg(not(feature = "no-pin"))]
-	assert_eq!(scoped_data.counter_notify.0.load(Ordering::Acquire), 2);
+	assert_eq!(scoped_data.header.counter.count(), 2);
 	#[cfg(feature = "no-pin")]
-	assert_eq!(Arc::strong_count(&scoped_data.counter_notify), 3);
+	assert_eq!(Arc::strong_count(&scoped_data.header), 3);
 	drop(data_ref);
 	drop(data_ref_2);
 	
@@ -160,8 +190,6 @@ fn test_std_traits() {
 #[cfg(feature = "runtime-tokio")]
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_std_traits() {
-	#[cfg(not(feature = "no-pin"))]
-	use std::sync::atomic::Ordering;
 	#[cfg(feature = "no-pin")]
 	use std::sync::Arc;
 	
@@ -179,10 +207,1507 @@ async fn test_std_traits() {
 	
 	let data_ref_2 = data_ref.clone();
 	#[cfg(not(feature = "no-pin"))]
-	assert_eq!(scoped_data.counter_notify.0.load(Ordering::Acquire), 2);
+	assert_eq!(scoped_data.header.counter.count(), 2);
 	#[cfg(feature = "no-pin")]
-	assert_eq!(Arc::strong_count(&scoped_data.counter_notify), 3);
+	assert_eq!(Arc::strong_count(&scoped_data.header), 3);
 	drop(data_ref);
 	drop(data_ref_2);
 	
 }
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_join_set() {
+	use std::{thread, time::Duration};
+	
+	make_type_connector!(SliceU8 = <'a> [u8]);
+	let data = vec!(1, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as SliceU8);
+	
+	let mut tasks = scoped_data.join_set();
+	tasks.spawn(|data_ref| {
+		thread::sleep(Duration::from_millis(100));
+		data_ref.inner()[0]
+	});
+	tasks.spawn(|data_ref| data_ref.inner()[1]);
+	assert_eq!(tasks.len(), 2);
+	assert_eq!(tasks.join_next().map(Result::ok), Some(Some(2)));
+	assert_eq!(tasks.join_next().map(Result::ok), Some(Some(1)));
+	assert!(tasks.join_next().is_none());
+	
+	tasks.spawn(|_| panic!("intentional panic"));
+	assert!(matches!(tasks.join_next(), Some(Err(_))));
+	
+	tasks.spawn(|data_ref| data_ref.inner()[2]);
+	assert_eq!(tasks.join_all(), vec!(3));
+	
+	// dropping the set doesn't resume panics, they're reported by the `ScopedRef` instead
+	let prev_panics = scoped_data.guard_panics().count;
+	{
+		let mut tasks = scoped_data.join_set::<()>();
+		tasks.spawn(|_data_ref| panic!("unjoined panic"));
+	}
+	assert_eq!(scoped_data.guard_panics().count, prev_panics + 1);
+	assert!(scoped_data.guard_panics().first_message.is_some());
+	
+	// closing the set gives the first panic back
+	let mut tasks = scoped_data.join_set::<()>();
+	tasks.spawn(|_data_ref| panic!("closed panic"));
+	tasks.spawn(|_data_ref| ());
+	let first_panic = tasks.close().expect("the panic should be returned by `close()`");
+	assert_eq!(first_panic.downcast_ref::<&str>(), Some(&"closed panic"));
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_join_set() {
+	use std::time::Duration;
+	
+	make_type_connector!(SliceU8 = <'a> [u8]);
+	let data = vec!(1, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as SliceU8);
+	
+	let mut tasks = scoped_data.join_set();
+	tasks.spawn(async |data_ref| {
+		tokio::time::sleep(Duration::from_millis(100)).await;
+		data_ref.inner()[0]
+	});
+	tasks.spawn_blocking(|data_ref| data_ref.inner()[1]);
+	assert_eq!(tasks.len(), 2);
+	assert_eq!(tasks.join_next().await.map(Result::ok), Some(Some(2)));
+	assert_eq!(tasks.join_next().await.map(Result::ok), Some(Some(1)));
+	assert!(tasks.join_next().await.is_none());
+	
+	tasks.spawn(async |_| panic!("intentional panic"));
+	assert!(matches!(tasks.join_next().await, Some(Err(_))));
+	
+	tasks.spawn(async |data_ref| data_ref.inner()[2]);
+	assert_eq!(tasks.join_all().await, vec!(3));
+	
+	// dropping the set doesn't resume panics, they're reported by the `ScopedRef` instead
+	let prev_panics = scoped_data.guard_panics().count;
+	{
+		let mut tasks = scoped_data.join_set::<()>();
+		tasks.spawn(async |_data_ref| panic!("unjoined panic"));
+	}
+	assert_eq!(scoped_data.guard_panics().count, prev_panics + 1);
+	assert!(scoped_data.guard_panics().first_message.is_some());
+	
+	// closing the set gives the first panic back
+	let mut tasks = scoped_data.join_set::<()>();
+	tasks.spawn(async |_data_ref| panic!("closed panic"));
+	tasks.spawn(async |_data_ref| ());
+	let first_panic = tasks.close().await.expect("the panic should be returned by `close()`");
+	assert_eq!(first_panic.downcast_ref::<&str>(), Some(&"closed panic"));
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_guard_panics() {
+	
+	capture_panic_messages();
+	make_type_connector!(U8 = <'a> u8);
+	let data = 123;
+	make_scoped_ref!(scoped_data = (&data) as U8);
+	
+	let data_ref = scoped_data.new_ref();
+	let _ = std::thread::spawn(move || {
+		let _data_ref = data_ref;
+		panic!("intentional panic");
+	}).join();
+	let data_ref = scoped_data.new_ref();
+	std::thread::spawn(move || drop(data_ref));
+	
+	let guard_panics = scoped_data.await_guards(None);
+	assert_eq!(guard_panics.count, 1);
+	assert_eq!(guard_panics.first_message.as_deref(), Some("intentional panic"));
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_guard_panics() {
+	
+	capture_panic_messages();
+	make_type_connector!(U8 = <'a> u8);
+	let data = 123;
+	make_scoped_ref!(scoped_data = (&data) as U8);
+	
+	let data_ref = scoped_data.new_ref();
+	let _ = tokio::spawn(async move {
+		let _data_ref = data_ref;
+		panic!("intentional panic");
+	}).await;
+	let data_ref = scoped_data.new_ref();
+	tokio::spawn(async move { drop(data_ref) });
+	
+	let guard_panics = scoped_data.await_guards(None).await;
+	assert_eq!(guard_panics.count, 1);
+	assert_eq!(guard_panics.first_message.as_deref(), Some("intentional panic"));
+	
+}
+
+
+
+#[cfg(all(feature = "rayon", feature = "runtime-none"))]
+#[test]
+fn test_rayon() {
+	use rayon::prelude::*;
+	
+	make_type_connector!(SliceU32 = <'a> [u32]);
+	let data = (0..1000).collect::<Vec<u32>>();
+	make_scoped_ref!(scoped_data = (&*data) as SliceU32);
+	
+	let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().expect("failed to build thread pool");
+	let sum = scoped_data.install(&pool, |data_ref| data_ref.par_iter().sum::<u32>());
+	assert_eq!(sum, 499500);
+	let chunk_sums = scoped_data.install(&pool, |data_ref| data_ref.par_chunks(100).map(|chunk| chunk.iter().sum::<u32>()).collect::<Vec<_>>());
+	assert_eq!(chunk_sums.len(), 10);
+	assert_eq!(chunk_sums.into_iter().sum::<u32>(), 499500);
+	
+}
+#[cfg(all(feature = "rayon", feature = "runtime-tokio"))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_rayon() {
+	use rayon::prelude::*;
+	
+	make_type_connector!(SliceU32 = <'a> [u32]);
+	let data = (0..1000).collect::<Vec<u32>>();
+	make_scoped_ref!(scoped_data = (&*data) as SliceU32);
+	
+	let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().expect("failed to build thread pool");
+	let sum = scoped_data.install(&pool, |data_ref| data_ref.par_iter().sum::<u32>()).await;
+	assert_eq!(sum, 499500);
+	let chunk_sums = scoped_data.install(&pool, |data_ref| data_ref.par_chunks(100).map(|chunk| chunk.iter().sum::<u32>()).collect::<Vec<_>>()).await;
+	assert_eq!(chunk_sums.len(), 10);
+	assert_eq!(chunk_sums.into_iter().sum::<u32>(), 499500);
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_worker_pool() {
+	use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+	
+	make_type_connector!(SliceU32 = <'a> [u32]);
+	let data = (0..1000).collect::<Vec<u32>>();
+	make_scoped_ref!(scoped_data = (&*data) as SliceU32);
+	
+	let pool = WorkerPool::new(4);
+	let sum = Arc::new(AtomicU32::new(0));
+	let sum_2 = sum.clone();
+	scoped_data.par_for_each(&pool, 64, move |x| { sum_2.fetch_add(*x, Ordering::Relaxed); });
+	assert_eq!(sum.load(Ordering::Relaxed), 499500);
+	
+	let (sender, receiver) = std::sync::mpsc::channel();
+	pool.execute(scoped_data.new_ref(), move |data| sender.send(data.len()).expect("failed to send"));
+	assert_eq!(receiver.recv().ok(), Some(1000));
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_worker_pool() {
+	use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+	
+	make_type_connector!(SliceU32 = <'a> [u32]);
+	let data = (0..1000).collect::<Vec<u32>>();
+	make_scoped_ref!(scoped_data = (&*data) as SliceU32);
+	
+	let pool = WorkerPool::new(4);
+	let sum = Arc::new(AtomicU32::new(0));
+	let sum_2 = sum.clone();
+	scoped_data.par_for_each(&pool, 64, move |x| { sum_2.fetch_add(*x, Ordering::Relaxed); }).await;
+	assert_eq!(sum.load(Ordering::Relaxed), 499500);
+	
+	let (sender, receiver) = tokio::sync::oneshot::channel();
+	pool.execute(scoped_data.new_ref(), move |data| sender.send(data.len()).expect("failed to send"));
+	assert_eq!(receiver.await.ok(), Some(1000));
+	
+}
+
+
+
+#[cfg(all(feature = "derive", feature = "runtime-none"))]
+#[test]
+fn test_derive() {
+	use std::thread;
+	
+	#[derive(TypeConnector)]
+	#[type_connector(crate = crate)]
+	struct Frame<'a> {
+		pixels: &'a [u8],
+	}
+	#[derive(TypeConnector)]
+	#[type_connector(crate = crate, name = RefTagged)]
+	struct Tagged<'a, 'b, T: Clone> where T: Send {
+		tag: &'a str,
+		value: &'b T,
+	}
+	
+	let pixels = vec!(1u8, 2, 3);
+	let frame = Frame { pixels: &pixels };
+	make_scoped_ref!(scoped_frame = (&frame) as ConnectorOf<Frame>);
+	let frame_ref: ScopedRefGuard<FrameConnector> = scoped_frame.new_ref();
+	let len = thread::spawn(move || frame_ref.inner().pixels.len()).join().expect("failed to join thread");
+	assert_eq!(len, 3);
+	
+	let value = 5u32;
+	let tagged = Tagged { tag: "five", value: &value };
+	make_scoped_ref!(scoped_tagged = (&tagged) as RefTagged<u32>);
+	let tagged_ref: ScopedRefGuard<ConnectorOf<Tagged<u32>>> = scoped_tagged.new_ref();
+	let (tag, value) = thread::spawn(move || (tagged_ref.inner().tag.to_string(), *tagged_ref.inner().value)).join().expect("failed to join thread");
+	assert_eq!((tag.as_str(), value), ("five", 5));
+	
+}
+#[cfg(all(feature = "derive", feature = "runtime-tokio"))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_derive() {
+	use std::thread;
+	
+	#[derive(TypeConnector)]
+	#[type_connector(crate = crate)]
+	struct Frame<'a> {
+		pixels: &'a [u8],
+	}
+	#[derive(TypeConnector)]
+	#[type_connector(crate = crate, name = RefTagged)]
+	struct Tagged<'a, 'b, T: Clone> where T: Send {
+		tag: &'a str,
+		value: &'b T,
+	}
+	
+	let pixels = vec!(1u8, 2, 3);
+	let frame = Frame { pixels: &pixels };
+	make_scoped_ref!(scoped_frame = (&frame) as ConnectorOf<Frame>);
+	let frame_ref: ScopedRefGuard<FrameConnector> = scoped_frame.new_ref();
+	let len = thread::spawn(move || frame_ref.inner().pixels.len()).join().expect("failed to join thread");
+	assert_eq!(len, 3);
+	
+	let value = 5u32;
+	let tagged = Tagged { tag: "five", value: &value };
+	make_scoped_ref!(scoped_tagged = (&tagged) as RefTagged<u32>);
+	let tagged_ref: ScopedRefGuard<ConnectorOf<Tagged<u32>>> = scoped_tagged.new_ref();
+	let (tag, value) = thread::spawn(move || (tagged_ref.inner().tag.to_string(), *tagged_ref.inner().value)).join().expect("failed to join thread");
+	assert_eq!((tag.as_str(), value), ("five", 5));
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_builtin_connectors() {
+	use std::{borrow::Borrow, path::Path, thread};
+	
+	fn total_len(data: impl AsRef<str>, more_data: impl Borrow<[u32]>) -> usize {
+		data.as_ref().len() + more_data.borrow().len()
+	}
+	
+	let text = String::from("Test Data");
+	let numbers = vec!(1u32, 2, 3);
+	let path = Path::new("some/path");
+	make_scoped_ref!(scoped_text = (&*text) as Str);
+	make_scoped_ref!(scoped_numbers = (&*numbers) as Slice<u32>);
+	make_scoped_ref!(scoped_path = (path) as PathRef);
+	make_scoped_ref!(scoped_string = (&text) as Ref<String>);
+	
+	let (text_ref, numbers_ref, path_ref, string_ref) = (scoped_text.new_ref(), scoped_numbers.new_ref(), scoped_path.new_ref(), scoped_string.new_ref());
+	let output = thread::spawn(move || {
+		assert!(text_ref.starts_with("Test")); // `Deref`
+		assert_eq!(numbers_ref.iter().sum::<u32>(), 6);
+		assert_eq!(path_ref.file_name().and_then(|name| name.to_str()), Some("path"));
+		assert_eq!(string_ref.capacity(), 9);
+		total_len(text_ref, numbers_ref)
+	}).join().expect("failed to join thread");
+	assert_eq!(output, 12);
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_builtin_connectors() {
+	use std::{borrow::Borrow, path::Path, thread};
+	
+	fn total_len(data: impl AsRef<str>, more_data: impl Borrow<[u32]>) -> usize {
+		data.as_ref().len() + more_data.borrow().len()
+	}
+	
+	let text = String::from("Test Data");
+	let numbers = vec!(1u32, 2, 3);
+	let path = Path::new("some/path");
+	make_scoped_ref!(scoped_text = (&*text) as Str);
+	make_scoped_ref!(scoped_numbers = (&*numbers) as Slice<u32>);
+	make_scoped_ref!(scoped_path = (path) as PathRef);
+	make_scoped_ref!(scoped_string = (&text) as Ref<String>);
+	
+	let (text_ref, numbers_ref, path_ref, string_ref) = (scoped_text.new_ref(), scoped_numbers.new_ref(), scoped_path.new_ref(), scoped_string.new_ref());
+	let output = thread::spawn(move || {
+		assert!(text_ref.starts_with("Test")); // `Deref`
+		assert_eq!(numbers_ref.iter().sum::<u32>(), 6);
+		assert_eq!(path_ref.file_name().and_then(|name| name.to_str()), Some("path"));
+		assert_eq!(string_ref.capacity(), 9);
+		total_len(text_ref, numbers_ref)
+	}).join().expect("failed to join thread");
+	assert_eq!(output, 12);
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_connector_inference() {
+	use std::thread;
+	
+	struct Borrowed<'a> {
+		inner: &'a u8,
+	}
+	make_type_connector!(RefBorrowed = <'a> Borrowed<'a>);
+	impl HasConnector for Borrowed<'_> {
+		type Connector = RefBorrowed;
+	}
+	
+	let number = 5u8;
+	let text = String::from("Test Data");
+	let borrowed = Borrowed { inner: &number };
+	make_scoped_ref!(scoped_number = &number);
+	make_scoped_ref!(scoped_text = &text);
+	make_scoped_ref!(scoped_str = text.as_str());
+	make_scoped_ref!(scoped_borrowed = &borrowed);
+	
+	let number_ref: ScopedRefGuard<Ref<u8>> = scoped_number.new_ref();
+	let text_ref: ScopedRefGuard<Ref<String>> = scoped_text.new_ref();
+	let str_ref: ScopedRefGuard<Str> = scoped_str.new_ref();
+	let borrowed_ref: ScopedRefGuard<RefBorrowed> = scoped_borrowed.new_ref();
+	let output = thread::spawn(move || format!("{} {} {} {}", *number_ref, *text_ref, &*str_ref, borrowed_ref.inner().inner)).join().expect("failed to join thread");
+	assert_eq!(output, "5 Test Data Test Data 5");
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_connector_inference() {
+	use std::thread;
+	
+	struct Borrowed<'a> {
+		inner: &'a u8,
+	}
+	make_type_connector!(RefBorrowed = <'a> Borrowed<'a>);
+	impl HasConnector for Borrowed<'_> {
+		type Connector = RefBorrowed;
+	}
+	
+	let number = 5u8;
+	let text = String::from("Test Data");
+	let borrowed = Borrowed { inner: &number };
+	make_scoped_ref!(scoped_number = &number);
+	make_scoped_ref!(scoped_text = &text);
+	make_scoped_ref!(scoped_str = text.as_str());
+	make_scoped_ref!(scoped_borrowed = &borrowed);
+	
+	let number_ref: ScopedRefGuard<Ref<u8>> = scoped_number.new_ref();
+	let text_ref: ScopedRefGuard<Ref<String>> = scoped_text.new_ref();
+	let str_ref: ScopedRefGuard<Str> = scoped_str.new_ref();
+	let borrowed_ref: ScopedRefGuard<RefBorrowed> = scoped_borrowed.new_ref();
+	let output = thread::spawn(move || format!("{} {} {} {}", *number_ref, *text_ref, &*str_ref, borrowed_ref.inner().inner)).join().expect("failed to join thread");
+	assert_eq!(output, "5 Test Data Test Data 5");
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_scoped_callback() {
+	use std::{thread, time::Duration, sync::{Arc, Mutex, atomic::{AtomicU32, Ordering}}};
+	
+	let registry: Arc<Mutex<Vec<ScopedCallback<u32, ()>>>> = Arc::default();
+	let total = AtomicU32::new(0);
+	{
+		let add = |amount: u32| { total.fetch_add(amount, Ordering::Relaxed); };
+		make_scoped_ref!(scoped_add = (&add) as DynFn<u32, ()>);
+		registry.lock().expect("failed to lock registry").push(scoped_add.new_callback());
+		
+		let registry = registry.clone();
+		thread::spawn(move || {
+			thread::sleep(Duration::from_millis(50));
+			let callbacks = std::mem::take(&mut *registry.lock().expect("failed to lock registry"));
+			for callback in &callbacks {
+				callback.call(5);
+				callback.clone().call(5);
+			}
+		});
+	} // blocks until the callbacks are removed from the registry
+	assert_eq!(total.load(Ordering::Relaxed), 10);
+	assert!(registry.lock().expect("failed to lock registry").is_empty());
+	
+	let local_data = [1, 2, 3];
+	let get = |(index, offset): (usize, i32)| local_data[index] + offset;
+	make_scoped_ref!(scoped_get = (&get) as DynFn<(usize, i32), i32>);
+	let callback = scoped_get.new_callback();
+	assert_eq!(thread::spawn(move || callback.call((1, 10))).join().expect("failed to join thread"), 12);
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_scoped_callback() {
+	use std::{thread, time::Duration, sync::{Arc, Mutex, atomic::{AtomicU32, Ordering}}};
+	
+	let registry: Arc<Mutex<Vec<ScopedCallback<u32, ()>>>> = Arc::default();
+	let total = AtomicU32::new(0);
+	{
+		let add = |amount: u32| { total.fetch_add(amount, Ordering::Relaxed); };
+		make_scoped_ref!(scoped_add = (&add) as DynFn<u32, ()>);
+		registry.lock().expect("failed to lock registry").push(scoped_add.new_callback());
+		
+		let registry = registry.clone();
+		thread::spawn(move || {
+			thread::sleep(Duration::from_millis(50));
+			let callbacks = std::mem::take(&mut *registry.lock().expect("failed to lock registry"));
+			for callback in &callbacks {
+				callback.call(5);
+				callback.clone().call(5);
+			}
+		});
+	} // blocks until the callbacks are removed from the registry
+	assert_eq!(total.load(Ordering::Relaxed), 10);
+	assert!(registry.lock().expect("failed to lock registry").is_empty());
+	
+	let local_data = [1, 2, 3];
+	let get = |(index, offset): (usize, i32)| local_data[index] + offset;
+	make_scoped_ref!(scoped_get = (&get) as DynFn<(usize, i32), i32>);
+	let callback = scoped_get.new_callback();
+	assert_eq!(thread::spawn(move || callback.call((1, 10))).join().expect("failed to join thread"), 12);
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_any_guard() {
+	use std::thread;
+	
+	let numbers = vec!(1u32, 2, 3);
+	let text = String::from("Test Data");
+	make_scoped_ref!(scoped_numbers = &*numbers);
+	make_scoped_ref!(scoped_text = &*text);
+	
+	let guards: Vec<AnyScopedRefGuard> = vec!(scoped_numbers.new_ref().into(), scoped_text.new_ref().into());
+	let keep_alive = guards[0].keep_alive();
+	let output = thread::spawn(move || {
+		assert!(guards[0].is::<Slice<u32>>());
+		assert!(guards[0].downcast_ref::<Str>().is_none());
+		let sum = guards[0].downcast_ref::<Slice<u32>>().expect("wrong connector").iter().sum::<u32>();
+		let text_ref = guards[1].clone().downcast::<Str>().expect("wrong connector");
+		format!("{sum} {text_ref}")
+	}).join().expect("failed to join thread");
+	assert_eq!(output, "6 Test Data");
+	assert!(scoped_numbers.has_active_guards());
+	drop(keep_alive);
+	assert!(!scoped_numbers.has_active_guards());
+	assert!(!scoped_text.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_any_guard() {
+	use std::thread;
+	
+	let numbers = vec!(1u32, 2, 3);
+	let text = String::from("Test Data");
+	make_scoped_ref!(scoped_numbers = &*numbers);
+	make_scoped_ref!(scoped_text = &*text);
+	
+	let guards: Vec<AnyScopedRefGuard> = vec!(scoped_numbers.new_ref().into(), scoped_text.new_ref().into());
+	let keep_alive = guards[0].keep_alive();
+	let output = thread::spawn(move || {
+		assert!(guards[0].is::<Slice<u32>>());
+		assert!(guards[0].downcast_ref::<Str>().is_none());
+		let sum = guards[0].downcast_ref::<Slice<u32>>().expect("wrong connector").iter().sum::<u32>();
+		let text_ref = guards[1].clone().downcast::<Str>().expect("wrong connector");
+		format!("{sum} {text_ref}")
+	}).join().expect("failed to join thread");
+	assert_eq!(output, "6 Test Data");
+	assert!(scoped_numbers.has_active_guards());
+	drop(keep_alive);
+	assert!(!scoped_numbers.has_active_guards());
+	assert!(!scoped_text.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_multiple_values() {
+	use std::thread;
+	
+	make_type_connector!(RefConfig = <'a> (u32, u32));
+	let config = (2u32, 10u32);
+	let input = vec!(1u32, 2, 3);
+	let names = String::from("Test Data");
+	make_scoped_ref!(scoped_data = (&config, &*input, &names) as (RefConfig, Slice<u32>, Str));
+	
+	let data_ref = scoped_data.new_ref();
+	let other_data_ref = data_ref.clone();
+	let output = thread::spawn(move || {
+		let (config, input, names) = *data_ref.inner();
+		let sum = input.iter().map(|value| value * config.0 + config.1).sum::<u32>();
+		format!("{sum} {names}")
+	}).join().expect("failed to join thread");
+	assert_eq!(output, "42 Test Data");
+	assert!(scoped_data.has_active_guards());
+	assert_eq!(other_data_ref.inner().1.len(), 3);
+	drop(other_data_ref);
+	assert!(!scoped_data.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_multiple_values() {
+	use std::thread;
+	
+	make_type_connector!(RefConfig = <'a> (u32, u32));
+	let config = (2u32, 10u32);
+	let input = vec!(1u32, 2, 3);
+	let names = String::from("Test Data");
+	make_scoped_ref!(scoped_data = (&config, &*input, &names) as (RefConfig, Slice<u32>, Str));
+	
+	let data_ref = scoped_data.new_ref();
+	let other_data_ref = data_ref.clone();
+	let output = thread::spawn(move || {
+		let (config, input, names) = *data_ref.inner();
+		let sum = input.iter().map(|value| value * config.0 + config.1).sum::<u32>();
+		format!("{sum} {names}")
+	}).join().expect("failed to join thread");
+	assert_eq!(output, "42 Test Data");
+	assert!(scoped_data.has_active_guards());
+	assert_eq!(other_data_ref.inner().1.len(), 3);
+	drop(other_data_ref);
+	assert!(!scoped_data.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_guard_set() {
+	use std::thread;
+	
+	let config = String::from("config");
+	make_scoped_ref!(scoped_config = &*config);
+	let guards = GuardSet::new().with(scoped_config.new_ref());
+	let output = {
+		let input = vec!(1u32, 2, 3);
+		let more_input = vec!(4u32);
+		make_scoped_ref!(scoped_input = &*input);
+		make_scoped_ref!(scoped_more_input = &*more_input);
+		// since `guards` is shared, this only adds the new guards to `job_guards`
+		let mut job_guards = guards.clone();
+		job_guards.extend([scoped_input.new_ref(), scoped_more_input.new_ref()]);
+		assert_eq!((guards.len(), job_guards.len()), (1, 3));
+		
+		thread::spawn(move || {
+			let config = job_guards.get::<Str>().expect("missing config");
+			let sum = job_guards.get_all::<Slice<u32>>().flatten().sum::<u32>();
+			assert!(job_guards.get::<Ref<u8>>().is_none());
+			format!("{config} {sum}")
+		}).join().expect("failed to join thread")
+	};
+	assert_eq!(output, "config 10");
+	drop(guards);
+	assert!(!scoped_config.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_guard_set() {
+	use std::thread;
+	
+	let config = String::from("config");
+	make_scoped_ref!(scoped_config = &*config);
+	let guards = GuardSet::new().with(scoped_config.new_ref());
+	let output = {
+		let input = vec!(1u32, 2, 3);
+		let more_input = vec!(4u32);
+		make_scoped_ref!(scoped_input = &*input);
+		make_scoped_ref!(scoped_more_input = &*more_input);
+		// since `guards` is shared, this only adds the new guards to `job_guards`
+		let mut job_guards = guards.clone();
+		job_guards.extend([scoped_input.new_ref(), scoped_more_input.new_ref()]);
+		assert_eq!((guards.len(), job_guards.len()), (1, 3));
+		
+		thread::spawn(move || {
+			let config = job_guards.get::<Str>().expect("missing config");
+			let sum = job_guards.get_all::<Slice<u32>>().flatten().sum::<u32>();
+			assert!(job_guards.get::<Ref<u8>>().is_none());
+			format!("{config} {sum}")
+		}).join().expect("failed to join thread")
+	};
+	assert_eq!(output, "config 10");
+	drop(guards);
+	assert!(!scoped_config.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_sub_scope() {
+	use std::{thread, time::Duration};
+	
+	struct Big {
+		name: String,
+		values: Vec<u32>,
+	}
+	make_type_connector!(RefBig = <'a> Big);
+	let big = Big { name: String::from("big"), values: vec!(1, 2, 3) };
+	make_scoped_ref!(scoped_big = (&big) as RefBig);
+	
+	let big_ref = scoped_big.new_ref();
+	let output = thread::spawn(move || {
+		let name = big_ref.inner().name.clone();
+		let sub_scope = big_ref.sub_scope::<Slice<u32>>(|big| &big.values);
+		let values_ref = sub_scope.new_ref();
+		thread::spawn(move || {
+			thread::sleep(Duration::from_millis(50));
+			drop(values_ref);
+		});
+		assert!(sub_scope.has_active_guards());
+		let sum = sub_scope.new_ref().iter().sum::<u32>();
+		drop(sub_scope); // blocks until `values_ref` is dropped
+		format!("{name} {sum}")
+	}).join().expect("failed to join thread");
+	assert_eq!(output, "big 6");
+	assert!(!scoped_big.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sub_scope() {
+	use std::time::Duration;
+	
+	struct Big {
+		name: String,
+		values: Vec<u32>,
+	}
+	make_type_connector!(RefBig = <'a> Big);
+	let big = Big { name: String::from("big"), values: vec!(1, 2, 3) };
+	make_scoped_ref!(scoped_big = (&big) as RefBig);
+	
+	let big_ref = scoped_big.new_ref();
+	let output = tokio::spawn(async move {
+		let name = big_ref.inner().name.clone();
+		let sub_scope = big_ref.sub_scope::<Slice<u32>>(|big| &big.values);
+		let values_ref = sub_scope.new_ref();
+		tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_millis(50)).await;
+			drop(values_ref);
+		});
+		assert!(sub_scope.has_active_guards());
+		let sum = sub_scope.new_ref().iter().sum::<u32>();
+		sub_scope.await_guards(None).await;
+		drop(sub_scope);
+		format!("{name} {sum}")
+	}).await.expect("failed to join task");
+	assert_eq!(output, "big 6");
+	assert!(!scoped_big.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_sub_scope_plain_thread() {
+	use std::{thread, time::Duration};
+	
+	let values = vec!(1, 2, 3);
+	make_scoped_ref!(scoped_values = (&values) as Slice<u32>);
+	
+	let sub_scope = scoped_values.new_ref().sub_scope::<Slice<u32>>(|values| &values[1..]);
+	let values_ref = sub_scope.new_ref();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		drop(values_ref);
+	});
+	thread::spawn(move || drop(sub_scope)).join().expect("failed to join thread"); // blocks until `values_ref` is dropped
+	assert!(!scoped_values.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sub_scope_plain_thread() {
+	use std::{thread, time::Duration};
+	
+	let values = vec!(1, 2, 3);
+	make_scoped_ref!(scoped_values = (&values) as Slice<u32>);
+	
+	let sub_scope = scoped_values.new_ref().sub_scope::<Slice<u32>>(|values| &values[1..]);
+	let values_ref = sub_scope.new_ref();
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		drop(values_ref);
+	});
+	// dropping on a thread without a runtime must still block until `values_ref` is dropped
+	let drop_thread = thread::spawn(move || drop(sub_scope));
+	tokio::task::spawn_blocking(move || drop_thread.join()).await.expect("failed to join task").expect("failed to join thread");
+	assert!(!scoped_values.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_scoped_owned() {
+	use std::{thread, time::Duration};
+	
+	struct Frame<'a> {
+		name: &'a str,
+		pixels: Vec<u8>,
+	}
+	make_type_connector!(FrameConnector = <'a> Frame<'a>);
+	let name = String::from("frame");
+	make_scoped_owned!(scoped_frame = (Frame { name: &name, pixels: vec!(1, 2, 3) }) as FrameConnector);
+	
+	let frame_ref = scoped_frame.new_ref();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		assert_eq!(frame_ref.inner().name, "frame");
+		assert_eq!(frame_ref.inner().pixels.len(), 3);
+	});
+	assert!(scoped_frame.has_active_guards());
+	let frame = scoped_frame.take_inner().expect("value was already taken"); // blocks until `frame_ref` is dropped
+	assert_eq!(frame.pixels, [1, 2, 3]);
+	assert!(scoped_frame.is_taken());
+	assert!(scoped_frame.take_inner().is_none());
+	
+	let owned = ScopedOwned::<Ref<String>>::new_static(String::from("deferred")).with_deferred_drop();
+	let text_ref = owned.new_ref();
+	drop(owned); // doesn't block even though `text_ref` is still alive
+	assert_eq!(*text_ref, "deferred");
+	
+	let owned = ScopedOwned::<Ref<String>>::new_static(String::from("sent"));
+	let text_ref = owned.new_ref();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		drop(text_ref);
+	});
+	thread::spawn(move || drop(owned)).join().expect("failed to join thread"); // blocks until `text_ref` is dropped
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_scoped_owned() {
+	use std::time::Duration;
+	
+	struct Frame<'a> {
+		name: &'a str,
+		pixels: Vec<u8>,
+	}
+	make_type_connector!(FrameConnector = <'a> Frame<'a>);
+	let name = String::from("frame");
+	make_scoped_owned!(scoped_frame = (Frame { name: &name, pixels: vec!(1, 2, 3) }) as FrameConnector);
+	
+	let frame_ref = scoped_frame.new_ref();
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		assert_eq!(frame_ref.inner().name, "frame");
+		assert_eq!(frame_ref.inner().pixels.len(), 3);
+	});
+	assert!(scoped_frame.has_active_guards());
+	let frame = scoped_frame.take_inner().await.expect("value was already taken");
+	assert_eq!(frame.pixels, [1, 2, 3]);
+	assert!(scoped_frame.is_taken());
+	assert!(scoped_frame.take_inner().await.is_none());
+	
+	let owned = ScopedOwned::<Ref<String>>::new_static(String::from("deferred")).with_deferred_drop();
+	let text_ref = owned.new_ref();
+	drop(owned); // doesn't block even though `text_ref` is still alive
+	assert_eq!(*text_ref, "deferred");
+	
+	// dropping on a thread without a runtime must still block (or defer the drop) instead of panicking
+	let owned = ScopedOwned::<Ref<String>>::new_static(String::from("sent"));
+	let deferred = ScopedOwned::<Ref<String>>::new_static(String::from("sent deferred")).with_deferred_drop();
+	let text_refs = (owned.new_ref(), deferred.new_ref());
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		drop(text_refs);
+	});
+	let drop_thread = std::thread::spawn(move || drop((deferred, owned)));
+	tokio::task::spawn_blocking(move || drop_thread.join()).await.expect("failed to join task").expect("failed to join thread");
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_reusable_scoped_ref() {
+	use std::{thread, time::Duration};
+	
+	#[cfg(not(feature = "no-pin"))]
+	let mut scope = std::pin::pin!(ReusableScopedRef::<Slice<u32>>::new());
+	#[cfg(feature = "no-pin")]
+	let mut scope = ReusableScopedRef::<Slice<u32>>::new();
+	let (sender, receiver) = std::sync::mpsc::channel();
+	
+	for frame in 1..=3 {
+		let frame_data = vec!(frame; 4);
+		rebind_scoped_ref!(scope, frame_scope = &*frame_data);
+		assert_eq!(frame_scope.generation(), frame as u64);
+		let frame_ref = frame_scope.new_ref();
+		let sender = sender.clone();
+		thread::spawn(move || {
+			thread::sleep(Duration::from_millis(20));
+			sender.send(frame_ref.iter().sum::<u32>()).expect("failed to send sum");
+		});
+	} // every frame blocks here until its guard is dropped
+	
+	assert_eq!(scope.generation(), 3);
+	drop(sender);
+	assert_eq!(receiver.iter().collect::<Vec<_>>(), [4, 8, 12]);
+	
+	#[cfg(debug_assertions)]
+	{
+		// a guard that outlives its binding (which the drop policy normally prevents) panics when it is used
+		let frame_data = vec!(4; 4);
+		rebind_scoped_ref!(scope, frame_scope = &*frame_data);
+		let stale_ref = frame_scope.new_ref();
+		frame_scope.scope.header.end_generation();
+		assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| stale_ref.len())).is_err());
+		frame_scope.scope.header.set_generation(4);
+	}
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_reusable_scoped_ref() {
+	use std::time::Duration;
+	
+	#[cfg(not(feature = "no-pin"))]
+	let mut scope = std::pin::pin!(ReusableScopedRef::<Slice<u32>>::new());
+	#[cfg(feature = "no-pin")]
+	let mut scope = ReusableScopedRef::<Slice<u32>>::new();
+	let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+	
+	for frame in 1..=3 {
+		let frame_data = vec!(frame; 4);
+		rebind_scoped_ref!(scope, frame_scope = &*frame_data);
+		assert_eq!(frame_scope.generation(), frame as u64);
+		let frame_ref = frame_scope.new_ref();
+		let sender = sender.clone();
+		tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_millis(20)).await;
+			sender.send(frame_ref.iter().sum::<u32>()).expect("failed to send sum");
+		});
+		frame_scope.await_guards(None).await;
+	}
+	
+	assert_eq!(scope.generation(), 3);
+	drop(sender);
+	let mut sums = vec!();
+	while let Some(sum) = receiver.recv().await { sums.push(sum); }
+	assert_eq!(sums, [4, 8, 12]);
+	
+	#[cfg(debug_assertions)]
+	{
+		// a guard that outlives its binding (which the drop policy normally prevents) panics when it is used
+		let frame_data = vec!(4; 4);
+		rebind_scoped_ref!(scope, frame_scope = &*frame_data);
+		let stale_ref = frame_scope.new_ref();
+		frame_scope.scope.header.end_generation();
+		assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| stale_ref.len())).is_err());
+		frame_scope.scope.header.set_generation(4);
+	}
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_scoped_swap() {
+	use std::{thread, time::Duration};
+	
+	let first = String::from("first");
+	let second = String::from("second");
+	make_scoped_swap!(config = (&first) as Str);
+	
+	let old_ref = config.load();
+	assert_eq!(config.publish(&second), 2);
+	assert_eq!(&*config.load(), "second");
+	assert_eq!(&*old_ref, "first");
+	assert_eq!(config.retired_versions(), 1);
+	
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		drop(old_ref);
+	});
+	config.await_retired(None);
+	assert_eq!(config.retired_versions(), 0);
+	assert_eq!(config.version(), 2);
+	assert!(!config.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_scoped_swap() {
+	use std::time::Duration;
+	
+	let first = String::from("first");
+	let second = String::from("second");
+	make_scoped_swap!(config = (&first) as Str);
+	
+	let old_ref = config.load();
+	assert_eq!(config.publish(&second), 2);
+	assert_eq!(&*config.load(), "second");
+	assert_eq!(&*old_ref, "first");
+	assert_eq!(config.retired_versions(), 1);
+	
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		drop(old_ref);
+	});
+	config.await_retired(None).await;
+	assert_eq!(config.retired_versions(), 0);
+	assert_eq!(config.version(), 2);
+	assert!(!config.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_max_guards() {
+	use std::{thread, time::Duration, sync::{Arc, atomic::{AtomicBool, Ordering}}};
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	scoped_data.set_max_guards(2);
+	assert_eq!(scoped_data.max_guards(), 2);
+	
+	let first_ref = scoped_data.acquire();
+	let second_ref = scoped_data.acquire();
+	let released = Arc::new(AtomicBool::new(false));
+	let released_2 = released.clone();
+	thread::spawn(move || {
+		thread::sleep(Duration::from_millis(50));
+		released_2.store(true, Ordering::Release);
+		drop(first_ref);
+	});
+	let third_ref = scoped_data.acquire(); // blocks until `first_ref` is dropped
+	assert!(released.load(Ordering::Acquire));
+	assert_eq!(second_ref.len() + third_ref.len(), 6);
+	
+	drop((second_ref, third_ref));
+	scoped_data.await_guards(None);
+	assert!(!scoped_data.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_max_guards() {
+	use std::{time::Duration, sync::{Arc, atomic::{AtomicBool, Ordering}}};
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	scoped_data.set_max_guards(2);
+	assert_eq!(scoped_data.max_guards(), 2);
+	
+	let first_ref = scoped_data.acquire().await;
+	let second_ref = scoped_data.acquire_blocking();
+	let released = Arc::new(AtomicBool::new(false));
+	let released_2 = released.clone();
+	tokio::spawn(async move {
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		released_2.store(true, Ordering::Release);
+		drop(first_ref);
+	});
+	let third_ref = scoped_data.acquire().await;
+	assert!(released.load(Ordering::Acquire));
+	assert_eq!(second_ref.len() + third_ref.len(), 6);
+	
+	drop((second_ref, third_ref));
+	scoped_data.await_guards(None).await;
+	assert!(!scoped_data.has_active_guards());
+	
+}
+
+
+
+#[cfg(all(feature = "runtime-none", not(feature = "sharded-counter")))] // the sharded counter never calls hooks
+#[test]
+fn test_hooks() {
+	use std::{thread, sync::{Arc, atomic::{AtomicU32, Ordering}}};
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let idle_count = Arc::new(AtomicU32::new(0));
+	let busy_count = Arc::new(AtomicU32::new(0));
+	let idle_count_2 = idle_count.clone();
+	let busy_count_2 = busy_count.clone();
+	scoped_data.on_idle(move || { idle_count_2.fetch_add(1, Ordering::AcqRel); });
+	scoped_data.on_busy(move || { busy_count_2.fetch_add(1, Ordering::AcqRel); });
+	
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = data_ref.clone();
+	assert_eq!((busy_count.load(Ordering::Acquire), idle_count.load(Ordering::Acquire)), (1, 0));
+	thread::spawn(move || drop(data_ref_2)).join().expect("failed to join thread");
+	drop(data_ref);
+	assert_eq!((busy_count.load(Ordering::Acquire), idle_count.load(Ordering::Acquire)), (1, 1));
+	
+	drop(scoped_data.new_ref());
+	assert_eq!((busy_count.load(Ordering::Acquire), idle_count.load(Ordering::Acquire)), (2, 2));
+	
+}
+#[cfg(all(feature = "runtime-tokio", not(feature = "sharded-counter")))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_hooks() {
+	use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let idle_count = Arc::new(AtomicU32::new(0));
+	let busy_count = Arc::new(AtomicU32::new(0));
+	let idle_count_2 = idle_count.clone();
+	let busy_count_2 = busy_count.clone();
+	scoped_data.on_idle(move || { idle_count_2.fetch_add(1, Ordering::AcqRel); });
+	scoped_data.on_busy(move || { busy_count_2.fetch_add(1, Ordering::AcqRel); });
+	
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = data_ref.clone();
+	assert_eq!((busy_count.load(Ordering::Acquire), idle_count.load(Ordering::Acquire)), (1, 0));
+	tokio::spawn(async move { drop(data_ref_2) }).await.expect("failed to join task");
+	drop(data_ref);
+	assert_eq!((busy_count.load(Ordering::Acquire), idle_count.load(Ordering::Acquire)), (1, 1));
+	
+	drop(scoped_data.acquire().await);
+	assert_eq!((busy_count.load(Ordering::Acquire), idle_count.load(Ordering::Acquire)), (2, 2));
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_builder() {
+	use std::{thread, time::Duration, sync::{Arc, atomic::{AtomicBool, Ordering}}};
+	
+	let data = vec!(1u32, 2, 3);
+	let went_idle = Arc::new(AtomicBool::new(false));
+	let went_idle_2 = went_idle.clone();
+	let builder = ScopedRef::builder()
+		.name("numbers")
+		.max_guards(4)
+		.drop_timeout(Duration::from_secs(10));
+	#[cfg(not(feature = "sharded-counter"))] // the sharded counter can't call hooks
+	let builder = builder.on_idle(move || went_idle_2.store(true, Ordering::Release));
+	#[cfg(feature = "sharded-counter")]
+	{
+		assert!(std::panic::catch_unwind(|| ScopedRef::<Str>::builder().on_idle(|| {})).is_err());
+		drop(went_idle_2);
+	}
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>, builder);
+	assert_eq!(scoped_data.name(), Some("numbers"));
+	assert_eq!(scoped_data.max_guards(), 4);
+	
+	let data_ref = scoped_data.acquire();
+	thread::spawn(move || assert_eq!(data_ref.len(), 3)).join().expect("failed to join thread");
+	assert_eq!(went_idle.load(Ordering::Acquire), cfg!(not(feature = "sharded-counter")));
+	
+	let text = String::from("text");
+	let mut scoped_text = unsafe { ScopedRef::<Str>::builder().name(format!("text {}", 1)).build_pinned(&*text) };
+	let text_ref = scoped_text.as_mut().new_ref();
+	assert_eq!(&*text_ref, "text");
+	assert_eq!(scoped_text.name(), Some("text 1"));
+	drop(text_ref);
+	drop(scoped_text);
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_builder() {
+	use std::{time::Duration, sync::{Arc, atomic::{AtomicBool, Ordering}}};
+	
+	let data = vec!(1u32, 2, 3);
+	let went_idle = Arc::new(AtomicBool::new(false));
+	let went_idle_2 = went_idle.clone();
+	let builder = ScopedRef::builder()
+		.name("numbers")
+		.max_guards(4)
+		.drop_timeout(Duration::from_secs(10));
+	#[cfg(not(feature = "sharded-counter"))] // the sharded counter can't call hooks
+	let builder = builder.on_idle(move || went_idle_2.store(true, Ordering::Release));
+	#[cfg(feature = "sharded-counter")]
+	{
+		assert!(std::panic::catch_unwind(|| ScopedRef::<Str>::builder().on_idle(|| {})).is_err());
+		drop(went_idle_2);
+	}
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>, builder);
+	assert_eq!(scoped_data.name(), Some("numbers"));
+	assert_eq!(scoped_data.max_guards(), 4);
+	
+	let data_ref = scoped_data.acquire().await;
+	tokio::spawn(async move { assert_eq!(data_ref.len(), 3) }).await.expect("failed to join task");
+	assert_eq!(went_idle.load(Ordering::Acquire), cfg!(not(feature = "sharded-counter")));
+	
+	let text = String::from("text");
+	let mut scoped_text = unsafe { ScopedRef::<Str>::builder().name(format!("text {}", 1)).build_pinned(&*text) };
+	let text_ref = scoped_text.as_mut().new_ref();
+	assert_eq!(&*text_ref, "text");
+	assert_eq!(scoped_text.name(), Some("text 1"));
+	drop(text_ref);
+	drop(scoped_text);
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_active_guard_count() {
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	assert_eq!(scoped_data.active_guard_count(), 0);
+	
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = data_ref.clone();
+	let keep_alive = data_ref.keep_alive();
+	assert_eq!(scoped_data.active_guard_count(), 3);
+	
+	scoped_data.set_max_guards(8); // flags are stored next to the count, so this checks that they aren't counted
+	assert_eq!(scoped_data.active_guard_count(), 3);
+	drop((data_ref, data_ref_2, keep_alive));
+	assert_eq!(scoped_data.active_guard_count(), 0);
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_active_guard_count() {
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	assert_eq!(scoped_data.active_guard_count(), 0);
+	
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = data_ref.clone();
+	let keep_alive = data_ref.keep_alive();
+	assert_eq!(scoped_data.active_guard_count(), 3);
+	
+	scoped_data.set_max_guards(8); // flags are stored next to the count, so this checks that they aren't counted
+	assert_eq!(scoped_data.active_guard_count(), 3);
+	drop((data_ref, data_ref_2, keep_alive));
+	assert_eq!(scoped_data.active_guard_count(), 0);
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_sharded_counter() {
+	use std::thread;
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let data_ref = scoped_data.new_ref();
+	let handles = (0..8).map(|_| {
+		let data_ref = data_ref.clone();
+		thread::spawn(move || {
+			let mut sum = 0;
+			for _ in 0..1000 {
+				let data_ref_2 = data_ref.clone();
+				sum += data_ref_2[0];
+			}
+			sum
+		})
+	}).collect::<Vec<_>>();
+	drop(data_ref);
+	
+	// guards are moved to other threads and dropped there, so the counter has to add up across threads
+	scoped_data.await_guards(None);
+	assert_eq!(scoped_data.active_guard_count(), 0);
+	for handle in handles {
+		assert_eq!(handle.join().expect("failed to join thread"), 1000);
+	}
+	
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = thread::spawn(move || data_ref.clone()).join().expect("failed to join thread");
+	assert_eq!(scoped_data.active_guard_count(), 1);
+	drop(data_ref_2);
+	assert!(!scoped_data.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_sharded_counter() {
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let data_ref = scoped_data.new_ref();
+	let handles = (0..8).map(|_| {
+		let data_ref = data_ref.clone();
+		tokio::spawn(async move {
+			let mut sum = 0;
+			for _ in 0..1000 {
+				let data_ref_2 = data_ref.clone();
+				sum += data_ref_2[0];
+				tokio::task::yield_now().await;
+			}
+			sum
+		})
+	}).collect::<Vec<_>>();
+	drop(data_ref);
+	
+	// guards are moved to other threads and dropped there, so the counter has to add up across threads
+	scoped_data.await_guards(None).await;
+	assert_eq!(scoped_data.active_guard_count(), 0);
+	for handle in handles {
+		assert_eq!(handle.await.expect("failed to join task"), 1000);
+	}
+	
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = tokio::spawn(async move { data_ref.clone() }).await.expect("failed to join task");
+	assert_eq!(scoped_data.active_guard_count(), 1);
+	drop(data_ref_2);
+	assert!(!scoped_data.has_active_guards());
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_guard_size() {
+	use std::{mem::size_of, sync::mpsc};
+	
+	assert_eq!(size_of::<ScopedRefGuard<Slice<u8>>>(), size_of::<usize>());
+	assert_eq!(size_of::<Option<ScopedRefGuard<DynFn<(), ()>>>>(), size_of::<usize>());
+	assert_eq!(size_of::<ScopedKeepAlive>(), size_of::<usize>());
+	
+	// the data pointer is read through the header, so wide pointers still have to work
+	let data = vec!(1u8, 2, 3);
+	make_scoped_ref!(scoped_data = &*data);
+	let (sender, receiver) = mpsc::channel();
+	sender.send(Some(scoped_data.new_ref())).expect("failed to send data guard");
+	let data_ref = receiver.recv().expect("failed to receive data guard").expect("data guard was lost");
+	assert_eq!(&*data_ref, &[1, 2, 3]);
+	let data_ref = AnyScopedRefGuard::new(data_ref);
+	assert_eq!(data_ref.downcast_ref::<Slice<u8>>(), Some(&[1u8, 2, 3][..]));
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_guard_size() {
+	use std::mem::size_of;
+	use tokio::sync::mpsc;
+	
+	assert_eq!(size_of::<ScopedRefGuard<Slice<u8>>>(), size_of::<usize>());
+	assert_eq!(size_of::<Option<ScopedRefGuard<DynFn<(), ()>>>>(), size_of::<usize>());
+	assert_eq!(size_of::<ScopedKeepAlive>(), size_of::<usize>());
+	
+	// the data pointer is read through the header, so wide pointers still have to work
+	let data = vec!(1u8, 2, 3);
+	make_scoped_ref!(scoped_data = &*data);
+	let (sender, mut receiver) = mpsc::unbounded_channel();
+	sender.send(Some(scoped_data.new_ref())).expect("failed to send data guard");
+	let data_ref = receiver.recv().await.expect("failed to receive data guard").expect("data guard was lost");
+	assert_eq!(&*data_ref, &[1, 2, 3]);
+	let data_ref = AnyScopedRefGuard::new(data_ref);
+	assert_eq!(data_ref.downcast_ref::<Slice<u8>>(), Some(&[1u8, 2, 3][..]));
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_concurrent_waiters() {
+	use std::{thread, time::Duration};
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let data_ref = scoped_data.new_ref();
+	let scoped_data = &*scoped_data;
+	thread::scope(|s| {
+		// every waiter has to be woken by the last guard
+		let waiters = (0..4).map(|_| s.spawn(|| scoped_data.await_guards(None))).collect::<Vec<_>>();
+		assert!(scoped_data.await_guards(Some(Duration::from_millis(10))).count == 0);
+		assert!(scoped_data.has_active_guards());
+		thread::sleep(Duration::from_millis(10));
+		drop(data_ref);
+		for waiter in waiters {
+			waiter.join().expect("failed to join thread");
+		}
+	});
+	
+	// the last guard of each scope is dropped right as the scope is being dropped
+	for _ in 0..1000 {
+		let data = vec!(1u32, 2, 3);
+		make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+		let data_ref = scoped_data.new_ref();
+		thread::spawn(move || drop(data_ref));
+	}
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_waiters() {
+	use std::time::Duration;
+	
+	let data = vec!(1u32, 2, 3);
+	make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let data_ref = scoped_data.new_ref();
+	let scoped_data = &*scoped_data;
+	// every waiter has to be woken by the last guard
+	let waiters = async {
+		tokio::join!(scoped_data.await_guards(None), scoped_data.await_guards(None), scoped_data.await_guards(None));
+	};
+	let dropper = async {
+		assert!(scoped_data.await_guards(Some(Duration::from_millis(10))).await.count == 0);
+		assert!(scoped_data.has_active_guards());
+		drop(data_ref);
+	};
+	tokio::join!(waiters, dropper);
+	
+	// the last guard of each scope is dropped right as the scope is being dropped
+	for _ in 0..1000 {
+		let data = vec!(1u32, 2, 3);
+		make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+		let data_ref = scoped_data.new_ref();
+		tokio::spawn(async move { drop(data_ref) });
+	}
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_waiter_stress() {
+	use std::{thread, time::{Duration, Instant}};
+	
+	// waiters register while the last guards are being released on other threads, so a missed waiter only wakes up at its timeout
+	for _ in 0..200 {
+		let data = vec!(1u32, 2, 3);
+		make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+		let data_refs = (0..4).map(|_| scoped_data.new_ref()).collect::<Vec<_>>();
+		let scoped_data = &*scoped_data;
+		thread::scope(|s| {
+			let waiters = (0..8).map(|_| s.spawn(|| {
+				let start = Instant::now();
+				scoped_data.await_guards(Some(Duration::from_secs(10)));
+				start.elapsed()
+			})).collect::<Vec<_>>();
+			for data_ref in data_refs {
+				s.spawn(move || drop(data_ref));
+			}
+			for waiter in waiters {
+				assert!(waiter.join().expect("failed to join thread") < Duration::from_secs(5));
+			}
+		});
+		assert!(!scoped_data.has_active_guards());
+	}
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_waiter_stress() {
+	use std::time::{Duration, Instant};
+	
+	// waiters register while the last guards are being released on other threads, so a missed waiter only wakes up at its timeout
+	for _ in 0..200 {
+		let data = vec!(1u32, 2, 3);
+		make_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+		let data_refs = (0..4).map(|_| scoped_data.new_ref()).collect::<Vec<_>>();
+		let scoped_data = &*scoped_data;
+		let wait = async || {
+			let start = Instant::now();
+			scoped_data.await_guards(Some(Duration::from_secs(10))).await;
+			assert!(start.elapsed() < Duration::from_secs(5));
+		};
+		for data_ref in data_refs {
+			tokio::spawn(async move { drop(data_ref) });
+		}
+		tokio::join!(wait(), wait(), wait(), wait());
+		assert!(!scoped_data.has_active_guards());
+	}
+	
+}
+
+
+
+#[cfg(feature = "runtime-none")]
+#[test]
+fn test_local_scoped_ref() {
+	use std::{future::Future, mem::size_of, pin::pin, sync::{Arc, atomic::{AtomicU32, Ordering}}, task::{Context, Poll, Wake, Waker}};
+	
+	struct CountWakes(AtomicU32);
+	impl Wake for CountWakes {
+		fn wake(self: Arc<Self>) {
+			self.0.fetch_add(1, Ordering::AcqRel);
+		}
+	}
+	
+	assert_eq!(size_of::<Option<LocalScopedRefGuard<Slice<u32>>>>(), size_of::<usize>());
+	let data = vec!(1u32, 2, 3);
+	make_local_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+	let data_ref = scoped_data.new_ref();
+	let data_ref_2 = data_ref.clone();
+	assert_eq!(scoped_data.active_guard_count(), 2);
+	assert_eq!(&*data_ref_2, &[1, 2, 3]);
+	
+	// there is no runtime here, so the future is polled by hand
+	let wakes = Arc::new(CountWakes(AtomicU32::new(0)));
+	let waker = Waker::from(wakes.clone());
+	let mut context = Context::from_waker(&waker);
+	let mut await_guards = pin!(scoped_data.await_guards());
+	assert!(await_guards.as_mut().poll(&mut context).is_pending());
+	drop(data_ref);
+	assert_eq!(wakes.0.load(Ordering::Acquire), 0);
+	drop(data_ref_2);
+	assert_eq!(wakes.0.load(Ordering::Acquire), 1);
+	assert_eq!(await_guards.as_mut().poll(&mut context), Poll::Ready(()));
+	assert!(!scoped_data.has_active_guards());
+	
+}
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_local_scoped_ref() {
+	use std::mem::size_of;
+	
+	assert_eq!(size_of::<Option<LocalScopedRefGuard<Slice<u32>>>>(), size_of::<usize>());
+	let local_set = tokio::task::LocalSet::new();
+	local_set.run_until(async {
+		let data = vec!(1u32, 2, 3);
+		make_local_scoped_ref!(scoped_data = (&*data) as Slice<u32>);
+		let data_ref = scoped_data.new_ref();
+		let tasks = (0..4).map(|_| {
+			let data_ref = data_ref.clone();
+			tokio::task::spawn_local(async move {
+				tokio::task::yield_now().await;
+				data_ref.iter().sum::<u32>()
+			})
+		}).collect::<Vec<_>>();
+		drop(data_ref);
+		assert!(scoped_data.has_active_guards());
+		
+		scoped_data.await_guards().await;
+		assert_eq!(scoped_data.active_guard_count(), 0);
+		for task in tasks {
+			assert_eq!(task.await.expect("failed to join task"), 6);
+		}
+	}).await;
+	
+}
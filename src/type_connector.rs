@@ -9,6 +9,10 @@
 
 
 
+use std::{ffi::{CStr, OsStr}, marker::PhantomData, path::Path};
+
+
+
 /// A type meant solely for enforcing type safety. To create this type, the [make_scoped_ref] macro is recommended
 pub trait TypeConnector: 'static {
 	/// This specifies the type that this `TypeConnector` represents, minus the leading `&` (so if you want to represent something like `&&u8`, this type should be `&u8`)
@@ -17,8 +21,94 @@ pub trait TypeConnector: 'static {
 
 
 
+/// Implemented for every [TypeConnector] that represents a slice (meaning `Super<'a>` is `[T]`), which allows slice-specific functionality like [ScopedRef::par_for_each()]
+pub trait SliceConnector: TypeConnector {
+	/// The type of the slice's elements
+	type Item;
+	/// Converts the referenced data to a slice (this is always a no-op)
+	fn as_slice<'a>(data: &'a Self::Super<'a>) -> &'a [Self::Item];
+}
+
+impl<ConnectorType, T> SliceConnector for ConnectorType where ConnectorType: for<'a> TypeConnector<Super<'a> = [T]> {
+	type Item = T;
+	#[inline]
+	fn as_slice<'a>(data: &'a Self::Super<'a>) -> &'a [T] {
+		data
+	}
+}
+
+
+
+/// Implemented for every [TypeConnector] whose referenced type doesn't use the lifetime of `Super` (like `String`, `[u8]`, or any of the built-in connectors such as [Ref] and [Str]), which allows `ScopedRefGuard` to implement `Deref` and `AsRef`
+pub trait StaticConnector: TypeConnector {
+	/// The referenced type, which is the same as `Super<'a>` for every lifetime
+	type Target: ?Sized;
+	/// Converts the referenced data to `Target` (this is always a no-op)
+	fn as_target<'a>(data: &'a Self::Super<'a>) -> &'a Self::Target;
+}
+
+impl<ConnectorType, T: ?Sized> StaticConnector for ConnectorType where ConnectorType: for<'a> TypeConnector<Super<'a> = T> {
+	type Target = T;
+	#[inline]
+	fn as_target<'a>(data: &'a Self::Super<'a>) -> &'a T {
+		data
+	}
+}
+
+
+
+/// Links a type to its canonical [TypeConnector], so that separate crates can agree on a single connector for the same type. This is usually implemented with `#[derive(TypeConnector)]` (which requires the "derive" feature)
+/// 
+/// The connector of a type can be named with [ConnectorOf]
+pub trait HasConnector {
+	/// The connector which represents `&Self`
+	type Connector: TypeConnector;
+}
+
+/// The canonical [TypeConnector] of `T` (see [HasConnector])
+pub type ConnectorOf<T> = <T as HasConnector>::Connector;
+
+impl<T: 'static> HasConnector for [T] {
+	type Connector = Slice<T>;
+}
+impl HasConnector for str {
+	type Connector = Str;
+}
+impl HasConnector for Path {
+	type Connector = PathRef;
+}
+impl HasConnector for OsStr {
+	type Connector = OsStrRef;
+}
+impl HasConnector for CStr {
+	type Connector = CStrRef;
+}
+
+/// Creates a [TypeConnector] for a struct, enum, or union, and implements [HasConnector] for it (requires the "derive" feature)
+/// 
+/// For a type named `Frame`, this creates a struct named `FrameConnector` (with the same visibility as `Frame`) which represents `&Frame`. Every lifetime of the type is set to the lifetime of `TypeConnector::Super`, and every type parameter is passed on to the connector (and must be `'static`). The name of the connector can be changed with `#[type_connector(name = OtherName)]`
+/// 
+/// ### Example:
+/// 
+/// ```ignore
+/// #[derive(TypeConnector)]
+/// pub struct Frame<'a> {
+///     pub pixels: &'a [u8],
+/// }
+/// 
+/// let pixels = vec!(0u8; 64);
+/// let scoped_data = ScopedRef::<ConnectorOf<Frame>>::new(&Frame { pixels: &pixels });
+/// // or: `ScopedRef::<FrameConnector>::new(..)`
+/// ```
+#[cfg(feature = "derive")]
+pub use scoped_ref_macros::TypeConnector;
+
+
+
 /// This is a utility for creating structs that implement [TypeConnector]
 /// 
+/// Note: for common types, the built-in connectors ([Ref], [Slice], [Str], [PathRef], [OsStrRef], [CStrRef], and [DynFn]) can be used instead
+/// 
 /// ### What is `TypeConnector` and why does it exist?
 /// 
 /// The `ScopedRef` and `ScopedRefGuard` structs need to share a generic type input so that type safety can be enforced, but using something like `&&u8` would cause `ScopedRefGuard` to be non-`'static`. That defeats the entire point of this crate, so instead, `ScopedRef` and `ScopedRefGuard` share a type that just represents the actual shared type.
@@ -27,6 +117,12 @@ pub trait TypeConnector: 'static {
 /// 
 /// ```ignore
 /// make_type_connector!(ConnectorTypeName = <'a> TypeToBeReferenced);
+/// 
+/// // or, with everything that is optional:
+/// make_type_connector!(
+///     #[attributes]
+///     pub ConnectorTypeName<T: Bound + other::Bound, U> = <'a, 'b> TypeToBeReferenced where T: Other<Bounds>
+/// );
 /// ```
 /// 
 /// There are three inputs, which are:
@@ -34,6 +130,13 @@ pub trait TypeConnector: 'static {
 /// - lifetime (the `<'a>`): This is for defining lifetimes within `TypeToBeReferenced`
 /// - TypeToBeReferenced: This is simple the type that you want to feed to `ScopedRef::new()`, but minus the leading `&`
 /// 
+/// And these are optional:
+/// - attributes: Any attributes (like `#[derive(..)]` or doc comments) are added to the helper struct
+/// - visibility: The visibility of the helper struct (private by default)
+/// - generics: Type parameters for the helper struct, which can be used in `TypeToBeReferenced`. These must be `'static` (since all `TypeConnector`s are `'static`), and each one can have bounds made of simple paths (like `T: Clone + std::fmt::Debug`). More complicated bounds (like `?Sized` or `Into<u8>`) can go in the where clause
+/// - more lifetimes: Any number of lifetimes can be given, but they will all be set to the same lifetime
+/// - where clause: This is added to the `TypeConnector` impl
+/// 
 /// ### Examples:
 /// 
 /// ```ignore
@@ -62,16 +165,159 @@ pub trait TypeConnector: 'static {
 /// make_type_connector!(RefSomeType = <'a> SomeType);
 /// let scoped_data = ScopedRef::<RefSomeType>::new(&SomeType);
 /// 
+/// // a public, generic connector with attributes
+/// make_type_connector!(
+///     /// Represents `&Vec<T>`
+///     #[derive(Debug)]
+///     pub VecOf<T: Clone> = <'a> Vec<T>
+/// );
+/// let scoped_data = ScopedRef::<VecOf<u8>>::new(&vec!(1, 2, 3));
+/// 
+/// // referencing a type with multiple lifetimes
+/// make_type_connector!(RefTwoRefs = <'a, 'b> TwoRefs<'a, 'b>);
+/// let scoped_data = ScopedRef::<RefTwoRefs>::new(&TwoRefs(&0, &1));
+/// 
 /// ```
 #[macro_export]
 macro_rules! make_type_connector {
-	($name:ident = <$lifetime:tt> $type:ty) => {
+	(
+		$(#[$attr:meta])*
+		$vis:vis $name:ident $(<$($param:ident $(: $first_bound:ident $(::$first_bound_path:ident)* $(+ $bound:ident $(::$bound_path:ident)*)*)?),+ $(,)?>)? = <$($lifetime:lifetime),+ $(,)?> $type:ty $(where $($where_clause:tt)+)?
+	) => {
 		
-		struct $name;
+		$(#[$attr])*
+		$vis struct $name $(<$($param),+>)? (::std::marker::PhantomData<fn() -> ($($($param,)+)?)>);
 		
-		impl TypeConnector for $name {
-			type Super<$lifetime> = $type;
-		}
+		const _: () = {
+			// this alias allows every given lifetime to be set to the single lifetime of `TypeConnector::Super`
+			type __ScopedRefSuper<$($lifetime),+ $(, $($param),+)?> = $type;
+			impl $(<$($param: 'static $(+ $first_bound $(::$first_bound_path)* $(+ $bound $(::$bound_path)*)*)?),+>)? $crate::TypeConnector for $name $(<$($param),+>)? where $($($where_clause)+)? {
+				type Super<'a> = $crate::__type_connector_super!(__ScopedRefSuper<> 'a [$($lifetime)+] [$($($param),+)?]);
+			}
+		};
 		
 	};
 }
+
+/// Used by [make_type_connector] to give the same lifetime to every lifetime parameter of a type alias
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __type_connector_super {
+	($alias:ident<$($args:lifetime,)*> $lifetime:lifetime [] [$($param:ident),*]) => {
+		$alias<$($args,)* $($param),*>
+	};
+	($alias:ident<$($args:lifetime,)*> $lifetime:lifetime [$_first:lifetime $($rest:lifetime)*] [$($param:ident),*]) => {
+		$crate::__type_connector_super!($alias<$($args,)* $lifetime,> $lifetime [$($rest)*] [$($param),*])
+	};
+}
+
+
+
+/// Represents `&T`, for any type `T` that doesn't have lifetimes (this includes unsized types like `[u8]` and `dyn Trait`)
+pub struct Ref<T: ?Sized + 'static> (PhantomData<fn() -> *const T>);
+
+impl<T: ?Sized + 'static> TypeConnector for Ref<T> {
+	type Super<'a> = T;
+}
+
+make_type_connector!(
+	/// Represents `&[T]`
+	pub Slice<T> = <'a> [T]
+);
+make_type_connector!(
+	/// Represents `&str`
+	pub Str = <'a> str
+);
+make_type_connector!(
+	/// Represents `&Path`
+	pub PathRef = <'a> Path
+);
+make_type_connector!(
+	/// Represents `&OsStr`
+	pub OsStrRef = <'a> OsStr
+);
+make_type_connector!(
+	/// Represents `&CStr`
+	pub CStrRef = <'a> CStr
+);
+make_type_connector!(
+	/// Represents `&(dyn Fn(Args) -> R + Send + Sync)`, which allows non-`'static` closures to be used in `'static` contexts (see [ScopedCallback](crate::ScopedCallback)). Functions with multiple arguments can use a tuple for `Args`
+	/// 
+	/// ### Example:
+	/// 
+	/// ```ignore
+	/// let local_data = vec!(1, 2, 3);
+	/// let callback = |index: usize| local_data[index];
+	/// make_scoped_ref!(scoped_callback = (&callback) as DynFn<usize, i32>);
+	/// let callback: ScopedCallback<usize, i32> = scoped_callback.new_callback(); // this is `'static`
+	/// assert_eq!(callback.call(1), 2);
+	/// ```
+	pub DynFn<Args, R> = <'a> dyn Fn(Args) -> R + Send + Sync + 'a
+);
+
+
+
+// A tuple of connectors represents a tuple of references, which allows multiple values to share a single `ScopedRef` (see `make_scoped_ref!()`)
+macro_rules! impl_tuple_connector {
+	($($connector:ident),+) => {
+		impl<$($connector: TypeConnector),+> TypeConnector for ($($connector,)+) {
+			type Super<'a> = ($(&'a $connector::Super<'a>,)+);
+		}
+	};
+}
+impl_tuple_connector!(A, B);
+impl_tuple_connector!(A, B, C);
+impl_tuple_connector!(A, B, C, D);
+impl_tuple_connector!(A, B, C, D, E);
+impl_tuple_connector!(A, B, C, D, E, F);
+impl_tuple_connector!(A, B, C, D, E, F, G);
+impl_tuple_connector!(A, B, C, D, E, F, G, H);
+
+
+
+// Used by `make_scoped_ref!(scope = &data)` to pick a connector for `data`. This uses autoref-based specialization: calling `(&Probe::of(data)).connector()` resolves to `ViaHasConnector` if the referenced type implements `HasConnector` (since that method takes `&Probe<T>` directly), and only falls back to `ViaRef` (which needs another autoref) otherwise
+#[doc(hidden)]
+pub mod __connector_inference {
+	use crate::*;
+	use std::marker::PhantomData;
+	
+	pub struct Probe<T: ?Sized> (PhantomData<fn() -> *const T>);
+	
+	impl<T: ?Sized> Probe<T> {
+		#[inline]
+		pub fn of(_data: &T) -> Self {
+			Self(PhantomData)
+		}
+	}
+	
+	pub trait ViaHasConnector {
+		type Connector: TypeConnector;
+		#[inline]
+		fn connector(&self) -> PhantomData<Self::Connector> {
+			PhantomData
+		}
+	}
+	impl<T: ?Sized + HasConnector> ViaHasConnector for Probe<T> {
+		type Connector = T::Connector;
+	}
+	
+	pub trait ViaRef {
+		type Connector: TypeConnector;
+		#[inline]
+		fn connector(&self) -> PhantomData<Self::Connector> {
+			PhantomData
+		}
+	}
+	impl<T: ?Sized + 'static> ViaRef for &Probe<T> {
+		type Connector = Ref<T>;
+	}
+	
+	/// # Safety
+	/// 
+	/// See [ScopedRef::new()]
+	#[inline]
+	pub unsafe fn new_scoped_ref<'a, ConnectorType: TypeConnector>(data: &'a ConnectorType::Super<'a>, _connector: PhantomData<ConnectorType>) -> ScopedRef<'a, ConnectorType> {
+		unsafe { ScopedRef::new(data) }
+	}
+	
+}